@@ -0,0 +1,375 @@
+use extendr_api::prelude::*;
+use crate::type_converter::TypeConverter;
+use std::fmt;
+use std::sync::Mutex;
+use wasmer::RuntimeError;
+
+/// Errors reported by the runtime, raised in R as classed conditions
+///
+/// Every condition inherits from `wasmer_error`, `error` and `condition`, and
+/// carries the module, instance and function names involved when known.
+#[derive(Debug)]
+pub enum WasmerError {
+    /// WAT parsing or module compilation failed (`wasmer_compile_error`)
+    Compile { module: Option<String>, message: String },
+    /// Instantiation failed, e.g. missing or mismatched imports (`wasmer_link_error`)
+    Link { module: Option<String>, instance: Option<String>, message: String },
+    /// A wasm function trapped or failed while running (`wasmer_trap`)
+    Trap { instance: Option<String>, function: Option<String>, message: String, trap: Box<TrapInfo> },
+    /// A module, instance, function or export could not be found (`wasmer_not_found`)
+    NotFound { kind: &'static str, name: String, instance: Option<String> },
+    /// An R value could not be used as requested (`wasmer_argument_error`)
+    Argument { function: Option<String>, message: String },
+    /// Any other runtime failure (`wasmer_runtime_error`)
+    Runtime { message: String },
+    /// A memory or table would exceed its maximum size (`wasmer_limit_error`)
+    Limit { resource: &'static str, requested: u64, limit: u64 },
+}
+
+pub type WasmerResult<T> = std::result::Result<T, WasmerError>;
+
+impl WasmerError {
+    pub fn compile(module: &str, message: impl fmt::Display) -> Self {
+        WasmerError::Compile { module: Some(module.to_string()), message: message.to_string() }
+    }
+
+    pub fn link(module: &str, instance: &str, message: impl fmt::Display) -> Self {
+        WasmerError::Link {
+            module: Some(module.to_string()),
+            instance: Some(instance.to_string()),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn trap(instance: &str, function: &str, error: RuntimeError) -> Self {
+        WasmerError::Trap {
+            instance: Some(instance.to_string()),
+            function: Some(function.to_string()),
+            message: error.message(),
+            trap: Box::new(TrapInfo::from_runtime_error(error)),
+        }
+    }
+
+    pub fn not_found(kind: &'static str, name: &str) -> Self {
+        WasmerError::NotFound { kind, name: name.to_string(), instance: None }
+    }
+
+    pub fn export_not_found(kind: &'static str, name: &str, instance: &str) -> Self {
+        WasmerError::NotFound { kind, name: name.to_string(), instance: Some(instance.to_string()) }
+    }
+
+    pub fn argument(message: impl fmt::Display) -> Self {
+        WasmerError::Argument { function: None, message: message.to_string() }
+    }
+
+    pub fn runtime(message: impl fmt::Display) -> Self {
+        WasmerError::Runtime { message: message.to_string() }
+    }
+
+    /// `resource` is `"memory"` (sizes in pages) or `"table"` (sizes in elements)
+    pub fn limit(resource: &'static str, requested: u64, limit: u64) -> Self {
+        WasmerError::Limit { resource, requested, limit }
+    }
+
+    /// Turn a trap into a more specific kind of trap, e.g. running out of fuel, which is raised
+    /// with `class` ahead of `wasmer_trap`. Other errors are returned unchanged.
+    pub fn with_trap_kind(self, class: &'static str, code: &str, message: impl fmt::Display) -> Self {
+        match self {
+            WasmerError::Trap { instance, function, mut trap, .. } => {
+                trap.class = Some(class);
+                trap.code = Some(code.to_string());
+                trap.from_host = false;
+                WasmerError::Trap { instance, function, message: message.to_string(), trap }
+            }
+            other => other,
+        }
+    }
+
+    /// R condition class specific to this error
+    pub fn class(&self) -> &'static str {
+        match self {
+            WasmerError::Compile { .. } => "wasmer_compile_error",
+            WasmerError::Link { .. } => "wasmer_link_error",
+            WasmerError::Trap { .. } => "wasmer_trap",
+            WasmerError::NotFound { .. } => "wasmer_not_found",
+            WasmerError::Argument { .. } => "wasmer_argument_error",
+            WasmerError::Runtime { .. } => "wasmer_runtime_error",
+            WasmerError::Limit { .. } => "wasmer_limit_error",
+        }
+    }
+
+    /// Build the R condition object: a list with `message`, `call` and the error fields.
+    /// A trap caused by an R error in a host function resurfaces the original condition
+    /// with the wasm fields added.
+    pub fn to_condition(&self) -> Robj {
+        if let WasmerError::Trap { instance, function, trap, .. } = self {
+            if let Some(condition) = &trap.host_condition {
+                return add_condition_fields(condition, vec![
+                    ("instance", instance.as_deref().map_or_else(|| r!(NULL), |i| r!(i))),
+                    ("function", function.as_deref().map_or_else(|| r!(NULL), |f| r!(f))),
+                    ("trap_code", r!(NA_STRING)),
+                    ("frames", trap.frames_to_r()),
+                    ("from_host", r!(true)),
+                ]);
+            }
+        }
+        let opt = |v: &Option<String>| match v {
+            Some(s) => r!(s.as_str()),
+            None => r!(NULL),
+        };
+        let mut names = vec!["message", "call"];
+        let mut values = vec![r!(self.to_string()), r!(NULL)];
+        match self {
+            WasmerError::Compile { module, .. } => {
+                names.push("module");
+                values.push(opt(module));
+            }
+            WasmerError::Link { module, instance, .. } => {
+                names.extend(["module", "instance"]);
+                values.extend([opt(module), opt(instance)]);
+            }
+            WasmerError::Trap { instance, function, trap, .. } => {
+                names.extend(["instance", "function", "trap_code", "frames", "from_host"]);
+                values.extend([
+                    opt(instance),
+                    opt(function),
+                    trap.code.as_deref().map_or_else(|| r!(NA_STRING), |code| r!(code)),
+                    trap.frames_to_r(),
+                    r!(trap.from_host),
+                ]);
+            }
+            WasmerError::NotFound { kind, name, instance } => {
+                names.extend(["kind", "name", "instance"]);
+                values.extend([r!(*kind), r!(name.as_str()), opt(instance)]);
+            }
+            WasmerError::Argument { function, .. } => {
+                names.push("function");
+                values.push(opt(function));
+            }
+            WasmerError::Runtime { .. } => {}
+            WasmerError::Limit { resource, requested, limit } => {
+                names.extend(["resource", "requested", "limit"]);
+                values.extend([r!(*resource), r!(*requested as f64), r!(*limit as f64)]);
+            }
+        }
+        let mut condition: Robj = List::from_names_and_values(names, values).unwrap().into();
+        let mut class = vec![self.class(), "wasmer_error", "error", "condition"];
+        if let WasmerError::Trap { trap, .. } = self {
+            if let Some(kind) = trap.class {
+                class.insert(0, kind);
+            }
+        }
+        condition.set_class(&class).unwrap();
+        condition
+    }
+}
+
+/// Entry points return their errors as the condition object; extendr hands it back to R, which
+/// raises it once `.Call` has returned (see `R/errors.R`), so no R error jumps over Rust frames
+impl From<WasmerError> for Robj {
+    fn from(error: WasmerError) -> Self {
+        error.to_condition()
+    }
+}
+
+impl fmt::Display for WasmerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WasmerError::Compile { module: Some(module), message } => {
+                write!(f, "Error compiling module '{}': {}", module, message)
+            }
+            WasmerError::Compile { module: None, message } => write!(f, "Error compiling module: {}", message),
+            WasmerError::Link { instance: Some(instance), message, .. } => {
+                write!(f, "Error creating instance '{}': {}", instance, message)
+            }
+            WasmerError::Link { instance: None, message, .. } => write!(f, "Error creating instance: {}", message),
+            WasmerError::Trap { function: Some(function), message, .. } => {
+                write!(f, "Error calling function '{}': {}", function, message)
+            }
+            WasmerError::Trap { function: None, message, .. } => write!(f, "Error calling function: {}", message),
+            WasmerError::NotFound { kind, name, instance: Some(instance) } => {
+                write!(f, "{} '{}' not found in instance '{}'", capitalize(kind), name, instance)
+            }
+            WasmerError::NotFound { kind, name, instance: None } => {
+                write!(f, "{} '{}' not found", capitalize(kind), name)
+            }
+            WasmerError::Argument { function: Some(function), message } => {
+                write!(f, "Invalid arguments for function '{}': {}", function, message)
+            }
+            WasmerError::Argument { function: None, message } => write!(f, "{}", message),
+            WasmerError::Runtime { message } => write!(f, "{}", message),
+            WasmerError::Limit { resource, requested, limit } => {
+                let unit = if *resource == "memory" { "pages" } else { "elements" };
+                write!(
+                    f,
+                    "{} of {} {} exceeds the maximum of {} {}",
+                    capitalize(resource),
+                    requested,
+                    unit,
+                    limit,
+                    unit
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for WasmerError {}
+
+/// One frame of a wasm backtrace
+#[derive(Debug, Clone)]
+pub struct WasmFrame {
+    pub module: String,
+    pub func_index: u32,
+    pub func_name: Option<String>,
+    pub func_offset: usize,
+    pub module_offset: usize,
+}
+
+/// Trap code and wasm backtrace of a failed call
+#[derive(Debug, Clone, Default)]
+pub struct TrapInfo {
+    /// Trap code such as `unreachable` or `integer_overflow`, `None` for errors raised by the host
+    pub code: Option<String>,
+    /// Wasm frames, innermost first
+    pub frames: Vec<WasmFrame>,
+    /// Whether the error was raised by a host function rather than by wasm code
+    pub from_host: bool,
+    /// The R condition raised by an R host function, if any
+    pub host_condition: Option<Robj>,
+    /// Condition class more specific than `wasmer_trap`, such as `wasmer_out_of_fuel`
+    pub class: Option<&'static str>,
+}
+
+impl TrapInfo {
+    pub fn from_runtime_error(error: RuntimeError) -> Self {
+        let frames = error
+            .trace()
+            .iter()
+            .map(|frame| WasmFrame {
+                module: frame.module_name().to_string(),
+                func_index: frame.func_index(),
+                func_name: frame.function_name().map(|name| name.to_string()),
+                func_offset: frame.func_offset(),
+                module_offset: frame.module_offset(),
+            })
+            .collect();
+        let host_condition = error.downcast_ref::<RHostError>().and_then(|e| e.take_condition());
+        let code = error.to_trap().map(|code| trap_code_name(&format!("{:?}", code)));
+        TrapInfo { from_host: code.is_none(), code, frames, host_condition, class: None }
+    }
+
+    /// Wasm frames as a data frame with columns module, func_index, func_name, func_offset and module_offset
+    pub fn frames_to_r(&self) -> Robj {
+        let module: Vec<String> = self.frames.iter().map(|f| f.module.clone()).collect();
+        let func_index: Vec<i32> = self.frames.iter().map(|f| f.func_index as i32).collect();
+        let func_name = Strings::from_values(self.frames.iter().map(|f| match &f.func_name {
+            Some(name) => Rstr::from(name.as_str()),
+            None => Rstr::na(),
+        }));
+        let func_offset: Vec<f64> = self.frames.iter().map(|f| f.func_offset as f64).collect();
+        let module_offset: Vec<f64> = self.frames.iter().map(|f| f.module_offset as f64).collect();
+        TypeConverter::data_frame(
+            &["module", "func_index", "func_name", "func_offset", "module_offset"],
+            vec![r!(module), r!(func_index), r!(func_name), r!(func_offset), r!(module_offset)],
+        )
+    }
+}
+
+/// An R error raised inside a host function. The error owns the condition, so it
+/// is released with the `RuntimeError` even when nobody takes it back.
+#[derive(Debug)]
+pub struct RHostError {
+    condition: Mutex<Option<Robj>>,
+    message: String,
+}
+
+// `RuntimeError::user` requires Send + Sync, but host functions run, and their
+// errors are handled and dropped, on the R thread that made the wasm call.
+unsafe impl Send for RHostError {}
+unsafe impl Sync for RHostError {}
+
+impl RHostError {
+    /// Keep the R condition and build the `RuntimeError` that aborts the wasm call
+    pub fn raise(condition: Robj) -> RuntimeError {
+        let message = condition
+            .dollar("message")
+            .ok()
+            .and_then(|m| m.as_str().map(|m| m.to_string()))
+            .unwrap_or_else(|| "R host function failed".to_string());
+        RuntimeError::user(Box::new(RHostError { condition: Mutex::new(Some(condition)), message }))
+    }
+
+    /// Take back the R condition
+    pub fn take_condition(&self) -> Option<Robj> {
+        self.condition.lock().ok()?.take()
+    }
+}
+
+impl fmt::Display for RHostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for RHostError {}
+
+/// Copy an R condition, setting the given fields and keeping its class
+fn add_condition_fields(condition: &Robj, fields: Vec<(&str, Robj)>) -> Robj {
+    let list = match condition.as_list() {
+        Some(list) => list,
+        None => return condition.clone(),
+    };
+    let mut names: Vec<String> = list.iter().map(|(name, _)| name.to_string()).collect();
+    let mut values: Vec<Robj> = list.iter().map(|(_, value)| value).collect();
+    for (name, value) in fields {
+        match names.iter().position(|n| n == name) {
+            Some(i) => values[i] = value,
+            None => {
+                names.push(name.to_string());
+                values.push(value);
+            }
+        }
+    }
+    let mut augmented: Robj = List::from_names_and_values(names, values).unwrap().into();
+    if let Some(class) = condition.class() {
+        augmented.set_class(class.collect::<Vec<_>>()).unwrap();
+    }
+    augmented
+}
+
+/// Map a `TrapCode` debug name to the snake case code exposed to R, using the
+/// names of the WebAssembly spec where they differ
+fn trap_code_name(debug_name: &str) -> String {
+    match debug_name {
+        "UnreachableCodeReached" => "unreachable".to_string(),
+        "HeapAccessOutOfBounds" => "out_of_bounds_memory".to_string(),
+        "TableAccessOutOfBounds" => "out_of_bounds_table".to_string(),
+        "IndirectCallToNull" => "indirect_call_to_null".to_string(),
+        "BadSignature" => "indirect_call_type_mismatch".to_string(),
+        "IntegerDivisionByZero" => "integer_divide_by_zero".to_string(),
+        "BadConversionToInteger" => "invalid_conversion_to_integer".to_string(),
+        other => {
+            let mut name = String::new();
+            for (i, ch) in other.chars().enumerate() {
+                if ch.is_uppercase() {
+                    if i > 0 {
+                        name.push('_');
+                    }
+                    name.extend(ch.to_lowercase());
+                } else {
+                    name.push(ch);
+                }
+            }
+            name
+        }
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
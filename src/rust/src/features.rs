@@ -0,0 +1,112 @@
+use wasmer::sys::CompilerConfig;
+use wasmer::wasmparser::WasmFeatures;
+use wasmer_types::{target::Target, Features};
+
+/// WebAssembly proposals that can be toggled per runtime, by their option name
+pub const FEATURE_NAMES: [&str; 9] = [
+    "simd",
+    "threads",
+    "bulk_memory",
+    "reference_types",
+    "multi_value",
+    "tail_call",
+    "exceptions",
+    "memory64",
+    "relaxed_simd",
+];
+
+/// Access to the proposals of wasmer's `Features` by name
+pub struct EngineFeatures;
+
+impl EngineFeatures {
+    pub fn get(features: &Features, name: &str) -> bool {
+        match name {
+            "simd" => features.simd,
+            "threads" => features.threads,
+            "bulk_memory" => features.bulk_memory,
+            "reference_types" => features.reference_types,
+            "multi_value" => features.multi_value,
+            "tail_call" => features.tail_call,
+            "exceptions" => features.exceptions,
+            "memory64" => features.memory64,
+            "relaxed_simd" => features.relaxed_simd,
+            _ => false,
+        }
+    }
+
+    fn set(features: &mut Features, name: &str, enabled: bool) {
+        match name {
+            "simd" => features.simd = enabled,
+            "threads" => features.threads = enabled,
+            "bulk_memory" => features.bulk_memory = enabled,
+            "reference_types" => features.reference_types = enabled,
+            "multi_value" => features.multi_value = enabled,
+            "tail_call" => features.tail_call = enabled,
+            "exceptions" => features.exceptions = enabled,
+            "memory64" => features.memory64 = enabled,
+            "relaxed_simd" => features.relaxed_simd = enabled,
+            _ => {}
+        }
+    }
+
+    /// Check that `name` is a known proposal
+    pub fn check_name(name: &str) -> std::result::Result<(), String> {
+        if FEATURE_NAMES.contains(&name) {
+            Ok(())
+        } else {
+            Err(format!("Unknown feature '{}'; available features: {}", name, FEATURE_NAMES.join(", ")))
+        }
+    }
+
+    /// Proposals the compiler supports and enables by default on `target`
+    pub fn for_compiler(compiler: &dyn CompilerConfig, target: &Target) -> (Features, Features) {
+        (
+            compiler.supported_features_for_target(target),
+            compiler.default_features_for_target(target),
+        )
+    }
+
+    /// The compiler's defaults with `overrides` applied, rejecting proposals it cannot compile
+    pub fn resolve(
+        compiler: &dyn CompilerConfig,
+        compiler_name: &str,
+        target: &Target,
+        overrides: &[(String, bool)],
+    ) -> std::result::Result<Features, String> {
+        let (supported, defaults) = Self::for_compiler(compiler, target);
+        for (name, enabled) in overrides {
+            if *enabled && !Self::get(&supported, name) {
+                return Err(format!(
+                    "The {} compiler does not support the '{}' feature on {}",
+                    compiler_name,
+                    name,
+                    target.triple()
+                ));
+            }
+        }
+        Ok(Self::apply(defaults, overrides))
+    }
+
+    /// `features` with `overrides` applied
+    pub fn apply(mut features: Features, overrides: &[(String, bool)]) -> Features {
+        for (name, enabled) in overrides {
+            Self::set(&mut features, name, *enabled);
+        }
+        features
+    }
+
+    /// Validator features matching `features`, as set by wasmer when it validates a module
+    pub fn for_validator(features: &Features) -> WasmFeatures {
+        let mut validator = WasmFeatures::default();
+        validator.set(WasmFeatures::SIMD, features.simd);
+        validator.set(WasmFeatures::THREADS, features.threads);
+        validator.set(WasmFeatures::BULK_MEMORY, features.bulk_memory);
+        validator.set(WasmFeatures::REFERENCE_TYPES, features.reference_types);
+        validator.set(WasmFeatures::MULTI_VALUE, features.multi_value);
+        validator.set(WasmFeatures::TAIL_CALL, features.tail_call);
+        validator.set(WasmFeatures::EXCEPTIONS, features.exceptions);
+        validator.set(WasmFeatures::MEMORY64, features.memory64);
+        validator.set(WasmFeatures::RELAXED_SIMD, features.relaxed_simd);
+        validator
+    }
+}
@@ -0,0 +1,229 @@
+use extendr_api::prelude::*;
+use extendr_api::wrapper::ExternalPtr;
+use wasmer::{Extern, ExternType, Function, Instance, Memory, Module, Store};
+use crate::error::{WasmerError, WasmerResult};
+use crate::introspection::ModuleIntrospection;
+use crate::type_converter::TypeConverter;
+use crate::wasi_utils::WasiState;
+use crate::WasmerRuntime;
+
+/// The wasm object a handle refers to
+#[derive(Clone)]
+pub enum HandleObject {
+    Module(Module),
+    Instance(Instance),
+    /// An exported function and the instance exporting it
+    Function(Function, Instance),
+    Memory(Memory),
+}
+
+/// A module, instance, function or memory together with the runtime owning its store.
+/// Holding the runtime pointer keeps the runtime alive for as long as R can reach the handle.
+pub struct WasmerHandle {
+    runtime: ExternalPtr<WasmerRuntime>,
+    /// Name of the module or instance, or the export name of a function or memory
+    name: String,
+    /// Instance a function or memory was exported from
+    instance: Option<String>,
+    object: HandleObject,
+    /// WASI environment of an instance whose module imports WASI
+    wasi: Option<WasiState>,
+}
+
+impl WasmerHandle {
+    pub fn new(runtime: ExternalPtr<WasmerRuntime>, name: &str, instance: Option<&str>, object: HandleObject) -> Self {
+        Self {
+            runtime,
+            name: name.to_string(),
+            instance: instance.map(|i| i.to_string()),
+            object,
+            wasi: None,
+        }
+    }
+
+    /// Attach the WASI environment of an instance
+    pub fn with_wasi(mut self, wasi: Option<WasiState>) -> Self {
+        self.wasi = wasi;
+        self
+    }
+
+    /// Wrap in an external pointer of class `c("wasmer_<kind>", "wasmer_handle")`
+    pub fn into_robj(self) -> Robj {
+        let class = format!("wasmer_{}", self.kind());
+        let mut robj: Robj = ExternalPtr::new(self).into();
+        robj.set_class(&[class.as_str(), "wasmer_handle"]).unwrap();
+        robj
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self.object {
+            HandleObject::Module(_) => "module",
+            HandleObject::Instance(_) => "instance",
+            HandleObject::Function(..) => "function",
+            HandleObject::Memory(_) => "memory",
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Instance name used in conditions raised through this handle
+    pub fn instance_name(&self) -> &str {
+        self.instance.as_deref().unwrap_or(&self.name)
+    }
+
+    /// The owning runtime, unless its resources have been released
+    pub fn runtime(&self) -> WasmerResult<ExternalPtr<WasmerRuntime>> {
+        let runtime = self.runtime.clone();
+        if runtime.as_ref().is_shutdown() {
+            return Err(WasmerError::runtime(format!(
+                "The runtime owning {} handle '{}' has been released",
+                self.kind(),
+                self.name
+            )));
+        }
+        Ok(runtime)
+    }
+
+    fn wrong_kind(&self, expected: &str) -> WasmerError {
+        WasmerError::argument(format!("Expected a wasmer_{} handle, got a wasmer_{} handle", expected, self.kind()))
+    }
+
+    pub fn module(&self) -> WasmerResult<&Module> {
+        match &self.object {
+            HandleObject::Module(module) => Ok(module),
+            _ => Err(self.wrong_kind("module")),
+        }
+    }
+
+    pub fn instance(&self) -> WasmerResult<&Instance> {
+        match &self.object {
+            HandleObject::Instance(instance) => Ok(instance),
+            _ => Err(self.wrong_kind("instance")),
+        }
+    }
+
+    /// The function and the instance exporting it
+    pub fn function(&self) -> WasmerResult<(&Function, &Instance)> {
+        match &self.object {
+            HandleObject::Function(func, instance) => Ok((func, instance)),
+            _ => Err(self.wrong_kind("function")),
+        }
+    }
+
+    pub fn memory(&self) -> WasmerResult<&Memory> {
+        match &self.object {
+            HandleObject::Memory(memory) => Ok(memory),
+            _ => Err(self.wrong_kind("memory")),
+        }
+    }
+
+    /// WASI environment of an instance handle
+    pub fn wasi(&self) -> WasmerResult<&WasiState> {
+        self.instance()?;
+        self.wasi.as_ref().ok_or_else(|| {
+            WasmerError::runtime(format!("Instance '{}' has no WASI state; its module does not import WASI", self.name))
+        })
+    }
+
+    /// Function and memory handles can be used as imports
+    pub fn as_extern(&self) -> Option<Extern> {
+        match &self.object {
+            HandleObject::Function(func, _) => Some(Extern::Function(func.clone())),
+            HandleObject::Memory(memory) => Some(Extern::Memory(memory.clone())),
+            _ => None,
+        }
+    }
+
+    /// Lines describing the handle and its type, as printed in R
+    pub fn describe(&self, store: &Store) -> Vec<String> {
+        match &self.object {
+            HandleObject::Module(module) => {
+                let mut lines = vec![format!("<wasmer_module> {}", self.name)];
+                lines.push("  imports:".to_string());
+                lines.extend(module.imports().map(|import| {
+                    format!("    {}.{}: {}", import.module(), import.name(), describe_extern_type(import.ty()))
+                }));
+                lines.push("  exports:".to_string());
+                lines.extend(describe_exports(module));
+                lines
+            }
+            HandleObject::Instance(instance) => {
+                let mut lines = vec![format!("<wasmer_instance> {}", self.name)];
+                if let Some(wasi) = &self.wasi {
+                    lines.push(format!("  environment: {}", wasi.env_type.name()));
+                }
+                lines.push("  exports:".to_string());
+                lines.extend(describe_exports(instance.module()));
+                lines
+            }
+            HandleObject::Function(func, _) => vec![format!(
+                "<wasmer_function> {}${}: {}",
+                self.instance_name(),
+                self.name,
+                describe_extern_type(&ExternType::Function(func.ty(store)))
+            )],
+            HandleObject::Memory(memory) => {
+                let view = memory.view(store);
+                vec![format!(
+                    "<wasmer_memory> {}${}: {}, currently {} pages ({} bytes)",
+                    self.instance_name(),
+                    self.name,
+                    describe_extern_type(&ExternType::Memory(memory.ty(store))),
+                    view.size().0,
+                    view.data_size()
+                )]
+            }
+        }
+    }
+}
+
+/// One line per export of `module`, leaving out the globals added by the runtime's middlewares
+fn describe_exports(module: &Module) -> Vec<String> {
+    module
+        .exports()
+        .filter(|export| !ModuleIntrospection::is_internal(export.name()))
+        .map(|export| format!("    {}: {}", export.name(), describe_extern_type(export.ty())))
+        .collect()
+}
+
+/// Human readable type of an import or export, e.g. `func (i32, i32) -> i32`
+pub fn describe_extern_type(ty: &ExternType) -> String {
+    let types = |types: &[wasmer::Type]| {
+        types.iter().map(|t| TypeConverter::type_name(*t)).collect::<Vec<_>>().join(", ")
+    };
+    match ty {
+        ExternType::Function(func_ty) => {
+            let results = match func_ty.results() {
+                [result] => TypeConverter::type_name(*result).to_string(),
+                results => format!("({})", types(results)),
+            };
+            format!("func ({}) -> {}", types(func_ty.params()), results)
+        }
+        ExternType::Global(global_ty) => format!(
+            "global {}{}",
+            if global_ty.mutability.is_mutable() { "mut " } else { "" },
+            TypeConverter::type_name(global_ty.ty)
+        ),
+        ExternType::Memory(memory_ty) => {
+            let mut desc = format!("memory {} pages", memory_ty.minimum.0);
+            if let Some(maximum) = memory_ty.maximum {
+                desc.push_str(&format!(", max {}", maximum.0));
+            }
+            if memory_ty.shared {
+                desc.push_str(", shared");
+            }
+            desc
+        }
+        ExternType::Table(table_ty) => {
+            let mut desc = format!("table {} {}", TypeConverter::type_name(table_ty.ty), table_ty.minimum);
+            if let Some(maximum) = table_ty.maximum {
+                desc.push_str(&format!(", max {}", maximum));
+            }
+            desc
+        }
+        #[allow(unreachable_patterns)]
+        other => format!("{:?}", other),
+    }
+}
@@ -0,0 +1,59 @@
+use extendr_api::prelude::*;
+use extendr_api::wrapper::ExternalPtr;
+use wasmer::{Extern, Function, Global, Imports, Memory, Table};
+use crate::handles::WasmerHandle;
+
+/// Utilities for building import objects from R values
+pub struct ImportUtils;
+
+impl ImportUtils {
+    /// Convert an external pointer made by the `wasmer_*_new_ext` functions to an `Extern`
+    pub fn extern_from_robj(value: &Robj) -> std::result::Result<Extern, String> {
+        if let Ok(func) = ExternalPtr::<Function>::try_from(value.clone()) {
+            return Ok(Extern::Function(func.as_ref().clone()));
+        }
+        if let Ok(memory) = ExternalPtr::<Memory>::try_from(value.clone()) {
+            return Ok(Extern::Memory(memory.as_ref().clone()));
+        }
+        if let Ok(table) = ExternalPtr::<Table>::try_from(value.clone()) {
+            return Ok(Extern::Table(table.as_ref().clone()));
+        }
+        if let Ok(global) = ExternalPtr::<Global>::try_from(value.clone()) {
+            return Ok(Extern::Global(global.as_ref().clone()));
+        }
+        if let Ok(handle) = ExternalPtr::<WasmerHandle>::try_from(value.clone()) {
+            return handle
+                .as_ref()
+                .as_extern()
+                .ok_or_else(|| format!("A wasmer_{} handle cannot be imported", handle.as_ref().kind()));
+        }
+        Err(format!(
+            "Expected a Function, Memory, Table or Global pointer or a function or memory handle, got {:?}",
+            value.rtype()
+        ))
+    }
+
+    /// Build an import object from a nested list such as
+    /// `list(env = list(foo = <Function>, mem = <Memory>))`
+    pub fn imports_from_list(imports: &List) -> std::result::Result<Imports, String> {
+        let mut import_object = Imports::new();
+        for (namespace, entries) in imports.iter() {
+            if namespace.is_empty() || namespace == "NA" {
+                return Err("All import namespaces must be named".to_string());
+            }
+            let entries = match entries.as_list() {
+                Some(list) => list,
+                None => return Err(format!("Import namespace '{}' must be a named list", namespace)),
+            };
+            for (name, value) in entries.iter() {
+                if name.is_empty() || name == "NA" {
+                    return Err(format!("All imports in namespace '{}' must be named", namespace));
+                }
+                let ext = Self::extern_from_robj(&value)
+                    .map_err(|e| format!("Import '{}.{}': {}", namespace, name, e))?;
+                import_object.define(namespace, name, ext);
+            }
+        }
+        Ok(import_object)
+    }
+}
@@ -0,0 +1,260 @@
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use wasmer::sys::{FunctionMiddleware, MiddlewareError, MiddlewareReaderState, ModuleMiddleware};
+use wasmer::wasmparser::{BlockType, Operator};
+use wasmer::sys::vm::{VMExtern, VMGlobalDefinition};
+use wasmer::{AsStoreMut, Extern, Global, GlobalType, Instance, Mutability, Store, Type, Value};
+use wasmer_types::{ExportIndex, GlobalIndex, GlobalInit, LocalFunctionIndex, ModuleInfo};
+
+/// Global added to every interruptible module; the code traps when it is non-zero
+pub const INTERRUPT_GLOBAL: &str = "wasmer_r_interrupt_requested";
+
+/// How often the watchdog looks for a user interrupt
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Set by the SIGINT handler installed while calls are watched, and by [`request_interrupt`]
+static INTERRUPT_RECEIVED: AtomicBool = AtomicBool::new(false);
+
+/// The watched calls in progress, nested in host functions or on other threads
+static WATCHED_CALLS: Mutex<WatchedCalls> = Mutex::new(WatchedCalls {
+    count: 0,
+    #[cfg(unix)]
+    previous_sigint: None,
+});
+
+/// Why a watchdog stopped a call
+#[derive(Debug, Clone, Copy)]
+pub enum StopReason {
+    /// The call ran longer than the given limit
+    Timeout(Duration),
+    /// The user pressed Ctrl-C (or Esc in RStudio)
+    Interrupt,
+}
+
+/// Stops a running wasm call on timeout or user interrupt.
+///
+/// Interruptible modules are compiled with a check at every function entry and loop header that
+/// traps once the instance's interrupt flag is set. While a call runs, a watchdog thread sets the
+/// flag when the deadline passes or an interrupt arrives; R cannot process interrupts itself until
+/// the call returns. The watchdog never touches the store: it only writes the flag's value, whose
+/// address is resolved on the calling thread before the call.
+pub struct CallWatchdog {
+    stop: mpsc::Sender<()>,
+    thread: JoinHandle<Option<StopReason>>,
+    flag: Global,
+}
+
+impl CallWatchdog {
+    /// Middleware to push on the compiler config of interruptible runtimes
+    pub fn middleware() -> Arc<dyn ModuleMiddleware> {
+        Arc::new(InterruptMiddleware { flag: Mutex::new(None) })
+    }
+
+    /// Start watching a call into `instance`; `None` for instances compiled without the interrupt checks
+    pub fn start(store: &mut Store, instance: &Instance, timeout: Option<Duration>) -> Option<Self> {
+        let flag = instance.exports.get_global(INTERRUPT_GLOBAL).ok()?.clone();
+        let value = FlagValue::resolve(store, &flag)?;
+        WatchedCalls::enter();
+
+        let deadline = timeout.and_then(|limit| Some((Instant::now().checked_add(limit)?, limit)));
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || {
+            let reason = loop {
+                let wait = match deadline {
+                    Some((at, _)) => at.saturating_duration_since(Instant::now()).min(POLL_INTERVAL),
+                    None => POLL_INTERVAL,
+                };
+                if stopped.recv_timeout(wait) != Err(mpsc::RecvTimeoutError::Timeout) {
+                    return None;
+                }
+                if user_interrupt_pending() {
+                    break StopReason::Interrupt;
+                }
+                if let Some((at, limit)) = deadline {
+                    if Instant::now() >= at {
+                        break StopReason::Timeout(limit);
+                    }
+                }
+            };
+            value.raise();
+            Some(reason)
+        });
+        Some(Self { stop, thread, flag })
+    }
+
+    /// Stop watching once the call returned, resetting the interrupt flag so the instance can be
+    /// called again. Returns why the watchdog stopped the call, if it did.
+    pub fn finish(self, store: &mut Store) -> Option<StopReason> {
+        let _ = self.stop.send(());
+        let reason = self.thread.join().unwrap_or(None);
+        WatchedCalls::leave();
+        let _ = self.flag.set(store, Value::I32(0));
+        reason
+    }
+}
+
+/// Count of the watched calls in progress. SIGINT is routed to the watchdogs from the start of
+/// the first call to the end of the last one, so that R's own handler is saved exactly once and
+/// restored whatever the order in which nested or concurrent calls finish.
+struct WatchedCalls {
+    count: usize,
+    #[cfg(unix)]
+    previous_sigint: Option<libc::sigaction>,
+}
+
+impl WatchedCalls {
+    /// Register a call; the first one forgets interrupts received before it and catches SIGINT
+    fn enter() {
+        let mut calls = WATCHED_CALLS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if calls.count == 0 {
+            INTERRUPT_RECEIVED.store(false, Ordering::SeqCst);
+            #[cfg(unix)]
+            {
+                calls.previous_sigint = Some(Self::catch_sigint());
+            }
+        }
+        calls.count += 1;
+    }
+
+    /// Unregister a call; the last one gives SIGINT back to R's handler
+    fn leave() {
+        let mut calls = WATCHED_CALLS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        calls.count -= 1;
+        #[cfg(unix)]
+        if calls.count == 0 {
+            if let Some(previous) = calls.previous_sigint.take() {
+                unsafe {
+                    libc::sigaction(libc::SIGINT, &previous, std::ptr::null_mut());
+                }
+            }
+        }
+    }
+
+    /// Route SIGINT to the watchdogs, returning R's handler
+    #[cfg(unix)]
+    fn catch_sigint() -> libc::sigaction {
+        extern "C" fn on_sigint(_signal: libc::c_int) {
+            INTERRUPT_RECEIVED.store(true, Ordering::SeqCst);
+        }
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_sigint as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            libc::sigemptyset(&mut action.sa_mask);
+            let mut previous: libc::sigaction = std::mem::zeroed();
+            libc::sigaction(libc::SIGINT, &action, &mut previous);
+            previous
+        }
+    }
+}
+
+/// Ask the call being watched to stop as if the user had interrupted it. Also used by host
+/// functions, and by the tests, to stop a call without sending a signal.
+pub fn request_interrupt() {
+    INTERRUPT_RECEIVED.store(true, Ordering::SeqCst);
+}
+
+/// Whether the user asked to interrupt R since the first watched call started. The request is
+/// left in place so that every watchdog sees it, including those of the calls a host function
+/// was called from.
+fn user_interrupt_pending() -> bool {
+    #[cfg(windows)]
+    {
+        // Rgui and RStudio signal interrupts through R's `UserBreak` flag rather than SIGINT;
+        // take it over so that R does not raise a second interrupt once the call returns
+        extern "C" {
+            static mut UserBreak: std::os::raw::c_int;
+        }
+        unsafe {
+            if UserBreak != 0 {
+                UserBreak = 0;
+                INTERRUPT_RECEIVED.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+    INTERRUPT_RECEIVED.load(Ordering::SeqCst)
+}
+
+/// The value of an instance's interrupt flag, handed to the watchdog thread.
+///
+/// A global's definition is allocated on its own when the instance is created and stays at the
+/// same address until the store is dropped, which cannot happen during the call; objects the call
+/// adds to the store do not move it. The value is only ever written as a whole `i32`.
+struct FlagValue(NonNull<VMGlobalDefinition>);
+
+unsafe impl Send for FlagValue {}
+
+impl FlagValue {
+    fn resolve(store: &mut Store, flag: &Global) -> Option<Self> {
+        match Extern::Global(flag.clone()).to_vm_extern().into_sys() {
+            VMExtern::Global(handle) => Some(Self(handle.get(store.objects_mut().as_sys()).vmglobal())),
+            _ => None,
+        }
+    }
+
+    /// Set the flag, which the wasm code sees at its next check
+    fn raise(&self) {
+        // The value is the first field of the definition, suitably aligned for an i32
+        let value = unsafe { &*(self.0.as_ptr() as *const AtomicI32) };
+        value.store(1, Ordering::SeqCst);
+    }
+}
+
+/// Module middleware adding the interrupt flag and its checks to every function. As with fuel
+/// metering, modules are compiled one at a time and the flag of the current module is replaced
+/// at the start of each compilation.
+#[derive(Debug)]
+struct InterruptMiddleware {
+    flag: Mutex<Option<GlobalIndex>>,
+}
+
+impl ModuleMiddleware for InterruptMiddleware {
+    fn generate_function_middleware(&self, _local_function_index: LocalFunctionIndex) -> Box<dyn FunctionMiddleware> {
+        let flag = self.flag.lock().unwrap().expect("module info is transformed before functions are compiled");
+        Box::new(FunctionInterruptChecks { flag, entered: false })
+    }
+
+    fn transform_module_info(&self, info: &mut ModuleInfo) -> std::result::Result<(), MiddlewareError> {
+        let flag = info.globals.push(GlobalType::new(Type::I32, Mutability::Var));
+        info.global_initializers.push(GlobalInit::I32Const(0));
+        info.exports.insert(INTERRUPT_GLOBAL.to_string(), ExportIndex::Global(flag));
+        *self.flag.lock().unwrap() = Some(flag);
+        Ok(())
+    }
+}
+
+/// Checks the interrupt flag on entry to a function and at the start of every loop iteration,
+/// which bounds the time between the flag being set and the code trapping
+#[derive(Debug)]
+struct FunctionInterruptChecks {
+    flag: GlobalIndex,
+    entered: bool,
+}
+
+impl FunctionInterruptChecks {
+    fn check(&self, state: &mut MiddlewareReaderState<'_>) {
+        state.extend(&[
+            Operator::GlobalGet { global_index: self.flag.as_u32() },
+            Operator::If { blockty: BlockType::Empty },
+            Operator::Unreachable,
+            Operator::End,
+        ]);
+    }
+}
+
+impl FunctionMiddleware for FunctionInterruptChecks {
+    fn feed<'a>(&mut self, operator: Operator<'a>, state: &mut MiddlewareReaderState<'a>) -> std::result::Result<(), MiddlewareError> {
+        if !self.entered {
+            self.entered = true;
+            self.check(state);
+        }
+        let is_loop = matches!(operator, Operator::Loop { .. });
+        state.push_operator(operator);
+        if is_loop {
+            self.check(state);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use extendr_api::prelude::*;
+use wasmer::{ExternType, Module};
+use crate::handles::describe_extern_type;
+use crate::interrupt::INTERRUPT_GLOBAL;
+use crate::metering::{POINTS_EXHAUSTED_GLOBAL, REMAINING_POINTS_GLOBAL};
+use crate::type_converter::TypeConverter;
+
+/// Columns describing import and export types, shared by both tables
+const TYPE_COLUMNS: [&str; 9] = ["kind", "type", "params", "results", "value_type", "mutable", "minimum", "maximum", "shared"];
+
+/// Globals the runtime's middlewares add to every module, which are not part of its interface
+const INTERNAL_EXPORTS: [&str; 3] = [INTERRUPT_GLOBAL, REMAINING_POINTS_GLOBAL, POINTS_EXHAUSTED_GLOBAL];
+
+/// Data frames describing what a compiled module imports and exports
+pub struct ModuleIntrospection;
+
+impl ModuleIntrospection {
+    /// One row per import: `module`, `name` and the type columns
+    pub fn imports(module: &Module) -> Robj {
+        let imports: Vec<_> = module.imports().collect();
+        let mut names = vec!["module", "name"];
+        names.extend(TYPE_COLUMNS);
+        let mut columns = vec![
+            r!(imports.iter().map(|import| import.module().to_string()).collect::<Vec<_>>()),
+            r!(imports.iter().map(|import| import.name().to_string()).collect::<Vec<_>>()),
+        ];
+        columns.extend(Self::type_columns(imports.iter().map(|import| import.ty())));
+        TypeConverter::data_frame(&names, columns)
+    }
+
+    /// One row per export: `name` and the type columns. The globals added by fuel metering and
+    /// interrupt checks are left out.
+    pub fn exports(module: &Module) -> Robj {
+        let exports: Vec<_> = module
+            .exports()
+            .filter(|export| !Self::is_internal(export.name()))
+            .collect();
+        let mut names = vec!["name"];
+        names.extend(TYPE_COLUMNS);
+        let mut columns = vec![r!(exports.iter().map(|export| export.name().to_string()).collect::<Vec<_>>())];
+        columns.extend(Self::type_columns(exports.iter().map(|export| export.ty())));
+        TypeConverter::data_frame(&names, columns)
+    }
+
+    /// Whether an export was added by the runtime's middlewares rather than the module itself
+    pub fn is_internal(name: &str) -> bool {
+        INTERNAL_EXPORTS.contains(&name)
+    }
+
+    /// Contents of the custom sections called `name`, in module order; a name may occur several times
+    pub fn custom_sections(module: &Module, name: &str) -> List {
+        List::from_values(module.custom_sections(name).map(|section| Raw::from_bytes(&section)).collect::<Vec<_>>())
+    }
+
+    /// Kind of an import or export, as used in WAT
+    fn kind(ty: &ExternType) -> &'static str {
+        match ty {
+            ExternType::Function(_) => "function",
+            ExternType::Global(_) => "global",
+            ExternType::Memory(_) => "memory",
+            ExternType::Table(_) => "table",
+            #[allow(unreachable_patterns)]
+            _ => "tag",
+        }
+    }
+
+    /// The [`TYPE_COLUMNS`]. Function signatures are list columns of type names; columns that do
+    /// not apply to a kind are `NA`.
+    fn type_columns<'a>(types: impl Iterator<Item = &'a ExternType>) -> Vec<Robj> {
+        let type_names = |types: &[wasmer::Type]| -> Robj {
+            r!(types.iter().map(|t| TypeConverter::type_name(*t)).collect::<Vec<_>>())
+        };
+        let no_types = || r!(Vec::<String>::new());
+        let mut kinds = Vec::new();
+        let mut descriptions = Vec::new();
+        let mut params = Vec::new();
+        let mut results = Vec::new();
+        let mut value_types = Vec::new();
+        let mut mutable = Vec::new();
+        let mut minimum = Vec::new();
+        let mut maximum = Vec::new();
+        let mut shared = Vec::new();
+        for ty in types {
+            kinds.push(Self::kind(ty));
+            descriptions.push(describe_extern_type(ty));
+            let (param_types, result_types) = match ty {
+                ExternType::Function(func_ty) => (type_names(func_ty.params()), type_names(func_ty.results())),
+                _ => (no_types(), no_types()),
+            };
+            params.push(param_types);
+            results.push(result_types);
+            let (value_type, is_mutable) = match ty {
+                ExternType::Global(global_ty) => (
+                    Rstr::from(TypeConverter::type_name(global_ty.ty)),
+                    Rbool::from(global_ty.mutability.is_mutable()),
+                ),
+                ExternType::Table(table_ty) => (Rstr::from(TypeConverter::type_name(table_ty.ty)), Rbool::na()),
+                _ => (Rstr::na(), Rbool::na()),
+            };
+            value_types.push(value_type);
+            mutable.push(is_mutable);
+            let (min, max, is_shared) = match ty {
+                ExternType::Memory(memory_ty) => (
+                    Some(memory_ty.minimum.0 as f64),
+                    memory_ty.maximum.map(|pages| pages.0 as f64),
+                    Rbool::from(memory_ty.shared),
+                ),
+                ExternType::Table(table_ty) => (
+                    Some(table_ty.minimum as f64),
+                    table_ty.maximum.map(|elements| elements as f64),
+                    Rbool::na(),
+                ),
+                _ => (None, None, Rbool::na()),
+            };
+            minimum.push(min.map_or(Rfloat::na(), Rfloat::from));
+            maximum.push(max.map_or(Rfloat::na(), Rfloat::from));
+            shared.push(is_shared);
+        }
+        vec![
+            r!(kinds),
+            r!(descriptions),
+            List::from_values(params).into(),
+            List::from_values(results).into(),
+            Strings::from_values(value_types).into(),
+            Logicals::from_values(mutable).into(),
+            Doubles::from_values(minimum).into(),
+            Doubles::from_values(maximum).into(),
+            Logicals::from_values(shared).into(),
+        ]
+    }
+}
@@ -1,24 +1,57 @@
-use wasmer::{Function, Store, Instance, FunctionEnv, FunctionEnvMut, Module, Value, imports, wat2wasm};
-use wasmer::{Table, TableType, Type};
+use wasmer::{Function, Store, Instance, FunctionEnv, FunctionEnvMut, Imports, Module, Value, imports, wat2wasm};
+use wasmer::{Global, Memory, MemoryType, Pages, Table, TableType, Type};
 use once_cell::sync::Lazy;
 use std::cell::RefCell;
 use extendr_api::prelude::*;
 use extendr_api::wrapper::ExternalPtr;
 use std::collections::HashMap;
+use std::time::Instant;
 use wasmer::{AsStoreRef, AsStoreMut};
-use wasmer::sys::EngineBuilder;
+use wasmer::sys::{EngineBuilder, NativeEngineExt};
 use std::sync::atomic::{AtomicU32, Ordering};
-use wasmer_wasix::WasiFunctionEnv;
 use memory::WasmerMemoryManager;
 use host_functions::WasmerHostFunctions;
-use type_converter::TypeConverter;
-use wasi_utils::WasiUtils;
+use type_converter::{Int64Mode, TypeConverter};
+use wasi_utils::{WasiConfig, WasiEnvType, WasiState, WasiUtils};
+use wasi_options::WasiOptions;
+use wasi_output::{OutputFormat, OutputStream};
+use wasi_fs::WasiFs;
 use compiler_utils::CompilerUtils;
+use error::{RHostError, TrapInfo, WasmerError, WasmerResult};
+use imports::ImportUtils;
+use vectorized::VectorizedCall;
+use handles::{HandleObject, WasmerHandle};
+use module_cache::{EngineSignature, ModuleCache, HEADLESS_COMPILER};
+use runtime_options::RuntimeOptions;
+use metering::{FuelMeter, MAX_FUEL};
+use interrupt::{request_interrupt, CallWatchdog, StopReason};
+use limits::ResourceLimits;
+use features::{EngineFeatures, FEATURE_NAMES};
+use target::EngineTarget;
+use introspection::ModuleIntrospection;
+use validation::{ModuleValidator, ValidationError};
 mod memory;
 mod host_functions;
 mod type_converter;
 mod wasi_utils;
 mod compiler_utils;
+mod error;
+mod imports;
+mod vectorized;
+mod handles;
+mod module_cache;
+mod runtime_options;
+mod metering;
+mod interrupt;
+mod limits;
+mod features;
+mod target;
+mod introspection;
+mod validation;
+mod wasi_fs;
+mod wasi_input;
+mod wasi_options;
+mod wasi_output;
 
 
 thread_local! {
@@ -40,9 +73,52 @@ fn register_r_function_internal(fun: Robj) -> u32 {
     id
 }
 
+thread_local! {
+    /// Calls an R function on a list of arguments, returning `list(TRUE, value)` or `list(FALSE, condition)`
+    static HOST_CALL_WRAPPER: Robj = eval_string(
+        "function(fun, args) tryCatch(list(TRUE, do.call(fun, args)), error = function(e) list(FALSE, e))"
+    ).unwrap();
+}
+
+/// Look up a registered R function by handle
+fn lookup_r_function(id: u32) -> std::result::Result<Robj, wasmer::RuntimeError> {
+    R_FUNCTION_REGISTRY
+        .with(|reg| reg.borrow().get(&id).cloned())
+        .ok_or_else(|| wasmer::RuntimeError::new(format!("R function for handle {} not found", id)))
+}
+
+/// Call an R function from a host function. An R error aborts the wasm call with a
+/// `RuntimeError` that keeps the original condition, see [RHostError].
+fn call_r_function(rfun: Robj, args: Vec<Robj>) -> std::result::Result<Robj, wasmer::RuntimeError> {
+    let outcome = HOST_CALL_WRAPPER
+        .with(|wrapper| wrapper.call(pairlist!(rfun, List::from_values(args))))
+        .map_err(|e| wasmer::RuntimeError::new(format!("R host function failed: {}", e)))?;
+    let outcome = outcome.as_list().unwrap();
+    let value = outcome.elt(1).unwrap();
+    if outcome.elt(0).unwrap().as_bool() == Some(true) {
+        Ok(value)
+    } else {
+        Err(RHostError::raise(value))
+    }
+}
+
+/// Call a registered R function by handle, see [call_r_function()]
+fn call_registered_r_function(id: u32, args: Vec<Robj>) -> std::result::Result<Robj, wasmer::RuntimeError> {
+    call_r_function(lookup_r_function(id)?, args)
+}
+
+/// Error for an R host function result that cannot be returned to wasm
+fn host_result_error(id: u32, expected: &str, value: &Robj) -> wasmer::RuntimeError {
+    wasmer::RuntimeError::new(format!(
+        "R function for handle {} returned {:?}, expected {}",
+        id,
+        value.rtype(),
+        expected
+    ))
+}
+
 pub fn register_r_function(_name: &str, fun: Robj) -> u32 {
-    let id = register_r_function_internal(fun);
-    id
+    register_r_function_internal(fun)
 }
 
 
@@ -71,95 +147,130 @@ pub struct WasmerEnv {
 
 
 pub fn create_generic_r_host_function(env: &FunctionEnv<WasmerEnv>, store: &mut Store) -> Function {
-    Function::new_typed_with_env(store, env, |mut env: FunctionEnvMut<WasmerEnv>, handle: i32, args_ptr: i32, argc: i32| -> i32 {
+    Function::new_typed_with_env(store, env, |mut env: FunctionEnvMut<WasmerEnv>, handle: i32, args_ptr: i32, argc: i32| -> std::result::Result<i32, wasmer::RuntimeError> {
         let _store_mut = env.as_store_mut();
         let (env_data, store_mut) = env.data_and_store_mut();
         let instance = match env_data.instance.as_ref() {
             Some(i) => i,
-            None => return 0,
+            None => return Err(wasmer::RuntimeError::new("r_host_call used before the instance was created")),
         };
         let store_ref = store_mut.as_store_ref();
         
         // Read arguments
         let args = read_i32_args_from_memory(instance, &store_ref, args_ptr, argc);
-        // Diagnostics
-        rprintln!("[wasmer] Host call: handle={}, args={:?}", handle, args);
-        
-        // Lookup and call R function
-        let result = R_FUNCTION_REGISTRY.with(|reg| {
-            reg.borrow().get(&(handle as u32)).cloned()
-        }).and_then(|rfun| {
-            rprintln!("[wasmer] Found R function for handle {}, calling...", handle);
-            if args.len() == 1 {
-                rfun.call(pairlist!(args[0])).ok()
-            } else {
-                let r_args = args.clone().into_iter().map(|x| r!(x)).collect::<Vec<Robj>>();
-                rfun.call(pairlist!(r_args)).ok()
-            }
-        });
-        if let Some(r) = result {
-            rprintln!("[wasmer] R call result: {:?}", r);
-            if let Some(val) = r.as_integer() {
-                rprintln!("[wasmer] Returning integer value: {}", val);
-                return val;
-            } else {
-                rprintln!("[wasmer] R function for handle {} did not return an integer: {:?}", handle, r);
-            }
+
+        // Lookup and call R function; R errors abort the wasm call
+        let rfun = lookup_r_function(handle as u32)?;
+        let r_args = if args.len() == 1 {
+            vec![r!(args[0])]
         } else {
-            rprintln!("[wasmer] R function for handle {} not found or call failed.", handle);
-        }
-        0 // fallback
+            vec![List::from_values(args.into_iter().map(|x| r!(x))).into()]
+        };
+        let r = call_r_function(rfun, r_args)?;
+        r.as_integer().ok_or_else(|| host_result_error(handle as u32, "an integer", &r))
     })
 }
 
 
 
-/// Helper function to convert Wasm values to R values
-fn convert_wasm_values_to_r(values: Box<[Value]>) -> Robj {
-    let mut r_values = Vec::new();
-    for value in values.iter() {
-        match value {
-            Value::I32(i) => r_values.push(r!(*i)),
-            Value::I64(i) => r_values.push(r!(*i as f64)),
-            Value::F32(f) => r_values.push(r!(*f as f64)),
-            Value::F64(f) => r_values.push(r!(*f)),
-            _ => r_values.push(r!(0)),
-        }
-    }
-    if r_values.len() == 1 {
-        r_values.into_iter().next().unwrap()
-    } else {
-        r!(r_values)
-    }
-}
-
 /// A Wasmer WebAssembly runtime wrapper for R
 pub struct WasmerRuntime {
     store: Store,
     modules: HashMap<String, Module>,
     instances: HashMap<String, Instance>,
+    linked_instances: HashMap<String, Instance>,
     r_function_registry: HashMap<String, Robj>,
     env: Option<FunctionEnv<WasmerEnv>>,
     #[allow(dead_code)]
     memory_manager: WasmerMemoryManager,
-    wasi_env: Option<WasiFunctionEnv>,
+    /// What the WASI environment of each WASI instance created afterwards is made from
+    wasi: Option<WasiConfig>,
+    /// WASI environments of the registered instances that import WASI, by instance name
+    wasi_instances: HashMap<String, WasiState>,
     shutdown: bool,
+    /// Compiler backing the store's engine, recorded in serialized artifacts and cache keys
+    compiler_name: String,
+    /// Artifact cache used by the `wasmer_compile_*` functions, if enabled
+    cache: Option<ModuleCache>,
+    /// Options the engine was built with
+    options: RuntimeOptions,
+    /// Proposals enabled in the engine
+    features: wasmer_types::Features,
 }
 
 impl WasmerRuntime {
     fn new() -> Self {
+        Self::build(Some("cranelift"), RuntimeOptions::default()).expect("cranelift is always enabled")
+    }
+
+    /// Build an engine for `compiler_name` with the middlewares required by `options`, or a
+    /// headless engine that can only load precompiled artifacts when `compiler_name` is `None`
+    fn build(compiler_name: Option<&str>, options: RuntimeOptions) -> std::result::Result<Self, String> {
+        let target = EngineTarget::resolve(options.target.as_deref(), &options.cpu_features)?;
+        let (builder, features) = match compiler_name {
+            Some(compiler_name) => {
+                let mut compiler_config = CompilerUtils::get_compiler_config(compiler_name)?;
+                if let Some(fuel) = options.fuel {
+                    compiler_config.push_middleware(FuelMeter::middleware(fuel));
+                }
+                if options.interruptible {
+                    compiler_config.push_middleware(CallWatchdog::middleware());
+                }
+                let features = EngineFeatures::resolve(compiler_config.as_ref(), compiler_name, &target, &options.features)?;
+                (EngineBuilder::new(compiler_config), features)
+            }
+            // Proposals only matter when validating wasm, which a headless engine never does
+            None => (EngineBuilder::headless(), EngineFeatures::apply(wasmer_types::Features::default(), &options.features)),
+        };
+        let mut engine = builder
+            .set_features(Some(features.clone()))
+            .set_target(Some(target))
+            .engine();
+        if let Some(limits) = ResourceLimits::from_options(&options, engine.target()) {
+            engine.set_tunables(limits);
+        }
+        let compiler_name = compiler_name.unwrap_or(HEADLESS_COMPILER);
+        Ok(Self::with_store(Store::new(engine), compiler_name, options, features))
+    }
+
+    fn with_store(store: Store, compiler_name: &str, options: RuntimeOptions, features: wasmer_types::Features) -> Self {
+        // The `wasmer.cache_dir` option enables the artifact cache for new runtimes
+        let cache = eval_string("getOption('wasmer.cache_dir')")
+            .ok()
+            .and_then(|dir| dir.as_str().map(ModuleCache::new));
         Self {
-            store: Store::default(),
+            store,
             modules: HashMap::new(),
             instances: HashMap::new(),
+            linked_instances: HashMap::new(),
             r_function_registry: HashMap::new(),
             env: None,
             memory_manager: WasmerMemoryManager::new(),
-            wasi_env: None,
+            wasi: None,
+            wasi_instances: HashMap::new(),
             shutdown: false,
+            compiler_name: compiler_name.to_lowercase(),
+            cache,
+            options,
+            features,
         }
     }
 
+    /// Compiler, target with its CPU features and code-changing options, which serialized
+    /// artifacts must match
+    fn engine_signature(&self) -> EngineSignature {
+        EngineSignature {
+            compiler: self.compiler_name.clone(),
+            target: EngineTarget::describe(self.store.engine().target()),
+            config: self.options.artifact_config(),
+        }
+    }
+
+    /// Whether the runtime was created without a compiler
+    fn is_headless(&self) -> bool {
+        self.compiler_name == HEADLESS_COMPILER
+    }
+
     /// Check if the runtime has been shutdown
     fn is_shutdown(&self) -> bool {
         self.shutdown
@@ -177,9 +288,11 @@ impl WasmerRuntime {
         if !runtime.is_shutdown() {
             runtime.modules.clear();
             runtime.instances.clear();
+            runtime.linked_instances.clear();
             runtime.r_function_registry.clear();
             runtime.env = None;
-            runtime.wasi_env = None;
+            runtime.wasi = None;
+            runtime.wasi_instances.clear();
             runtime.set_shutdown();
         }
         // No need to manually set the external pointer address to NULL.
@@ -190,9 +303,11 @@ impl WasmerRuntime {
     pub fn shutdown(&mut self) {
         self.modules.clear();
         self.instances.clear();
+        self.linked_instances.clear();
         self.r_function_registry.clear();
         self.env = None;
-        self.wasi_env = None;
+        self.wasi = None;
+        self.wasi_instances.clear();
         // Optionally drop memory manager resources if needed
     }
 }
@@ -215,137 +330,271 @@ impl WasmerRuntime {
 pub fn wasmer_runtime_release_ressources(mut ptr: ExternalPtr<WasmerRuntime>) {
     WasmerRuntime::shutdown_and_finalize(&mut ptr);
 }
-fn wasmer_compile_wat(runtime: &mut WasmerRuntime, wat_code: String, module_name: String) -> String {
-    match wat2wasm(wat_code.as_bytes()) {
-        Ok(wasm_bytes) => {
-            match Module::new(&runtime.store, wasm_bytes) {
-                Ok(module) => {
-                    runtime.modules.insert(module_name.clone(), module);
-                    format!("Module '{}' compiled successfully", module_name)
-                }
-                Err(e) => format!("Error compiling module: {}", e),
-            }
+fn wasmer_compile_wat(runtime: &mut WasmerRuntime, wat_code: String, module_name: String) -> WasmerResult<String> {
+    let wasm_bytes = wat2wasm(wat_code.as_bytes())
+        .map_err(|e| WasmerError::compile(&module_name, format!("invalid WAT: {}", e)))?;
+    let (module, cached) = compile_module(runtime, &wasm_bytes, &module_name)?;
+    runtime.modules.insert(module_name.clone(), module);
+    if cached {
+        return Ok(format!("Module '{}' loaded from cache", module_name));
+    }
+    Ok(format!("Module '{}' compiled successfully", module_name))
+}
+
+/// Compile wasm bytes, going through the runtime's artifact cache when one is enabled.
+/// Also returns whether the module was loaded from the cache.
+fn compile_module(runtime: &WasmerRuntime, wasm_bytes: &[u8], module_name: &str) -> WasmerResult<(Module, bool)> {
+    if runtime.is_headless() {
+        return Err(WasmerError::compile(
+            module_name,
+            "the runtime is headless and cannot compile modules; load a precompiled module with wasmer_module_deserialize_ext()",
+        ));
+    }
+    let engine = runtime.engine_signature();
+    if let Some(cache) = &runtime.cache {
+        if let Some(module) = cache.load(&runtime.store, wasm_bytes, &engine) {
+            return Ok((module, true));
         }
-        Err(e) => format!("Error converting WAT to WASM: {}", e),
     }
+    let module = Module::new(&runtime.store, wasm_bytes).map_err(|e| WasmerError::compile(module_name, e))?;
+    if let Some(cache) = &runtime.cache {
+        // A cache entry that cannot be written only costs a recompilation next time
+        let _ = cache.store(&module, wasm_bytes, &engine);
+    }
+    Ok((module, false))
+}
+
+/// Look up an instance by name
+fn lookup_instance<'a>(instances: &'a HashMap<String, Instance>, instance_name: &str) -> WasmerResult<&'a Instance> {
+    instances
+        .get(instance_name)
+        .ok_or_else(|| WasmerError::not_found("instance", instance_name))
+}
+
+/// Look up a compiled module by name
+fn lookup_module<'a>(modules: &'a HashMap<String, Module>, module_name: &str) -> WasmerResult<&'a Module> {
+    modules
+        .get(module_name)
+        .ok_or_else(|| WasmerError::not_found("module", module_name))
+}
+
+/// Look up an exported memory of an instance by name
+fn lookup_memory(instances: &HashMap<String, Instance>, instance_name: &str, memory_name: &str) -> WasmerResult<Memory> {
+    lookup_instance(instances, instance_name)?
+        .exports
+        .get_memory(memory_name)
+        .cloned()
+        .map_err(|_| WasmerError::export_not_found("memory", memory_name, instance_name))
+}
+
+/// Map an instantiation failure to a link error, or a trap when the start function failed
+fn instantiation_error(module_name: &str, instance_name: &str, error: wasmer::InstantiationError) -> WasmerError {
+    match error {
+        wasmer::InstantiationError::Start(e) => WasmerError::Trap {
+            instance: Some(instance_name.to_string()),
+            function: None,
+            message: format!("start function trapped: {}", e.message()),
+            trap: Box::new(TrapInfo::from_runtime_error(e)),
+        },
+        e => WasmerError::link(module_name, instance_name, e),
+    }
+}
+
+/// Instantiate a module with the given imports, resolving the remaining imports from the
+/// registered instances and giving it its own WASI environment when a WASI state exists
+fn instantiate_module(runtime: &mut WasmerRuntime, module_name: &str, instance_name: &str, import_object: Imports) -> WasmerResult<Instance> {
+    let module = match runtime.modules.get(module_name) {
+        Some(module) => module.clone(),
+        None => return Err(WasmerError::not_found("module", module_name)),
+    };
+    instantiate_compiled(runtime, &module, module_name, instance_name, import_object)
+}
+
+/// Instantiate an already resolved module and register the instance, and its WASI environment
+/// if it has one, under `instance_name`, which must not be in use
+fn instantiate_compiled(runtime: &mut WasmerRuntime, module: &Module, module_name: &str, instance_name: &str, import_object: Imports) -> WasmerResult<Instance> {
+    if runtime.instances.contains_key(instance_name) {
+        return Err(WasmerError::argument(format!(
+            "Instance '{}' already exists; remove it with wasmer_instance_remove_ext() or choose another name",
+            instance_name
+        )));
+    }
+    let wasi = runtime.wasi.clone();
+    let (instance, wasi) = link_instance(runtime, module, module_name, instance_name, import_object, wasi.as_ref())?;
+    runtime.instances.insert(instance_name.to_string(), instance.clone());
+    if let Some(wasi) = wasi {
+        runtime.wasi_instances.insert(instance_name.to_string(), wasi);
+    }
+    Ok(instance)
 }
 
-fn wasmer_instantiate(runtime: &mut WasmerRuntime, module_name: String, instance_name: String) -> String {
-    if let Some(module) = runtime.modules.get(&module_name) {
-        let env = FunctionEnv::new(&mut runtime.store, WasmerEnv { instance: None });
-        let mut import_object = imports! {
-            "env" => {
-                "r_host_call" => create_generic_r_host_function(&env, &mut runtime.store),
+/// Instantiate a module with the given imports and the exports of registered instances, without
+/// registering the instance; `instance_name` is only used in errors. A module importing WASI gets
+/// a new WASI environment created from `wasi`, which is returned with the instance.
+fn link_instance(
+    runtime: &mut WasmerRuntime,
+    module: &Module,
+    module_name: &str,
+    instance_name: &str,
+    import_object: Imports,
+    wasi: Option<&WasiConfig>,
+) -> WasmerResult<(Instance, Option<WasiState>)> {
+    let target = runtime.store.engine().target();
+    if !EngineTarget::is_host_compatible(target) {
+        return Err(WasmerError::link(module_name, instance_name, format!(
+            "the runtime compiles for '{}', which this machine cannot run; serialize the module with \
+             wasmer_module_serialize_ext() and load it on a matching machine",
+            EngineTarget::describe(target)
+        )));
+    }
+
+    // Exports of registered instances come first so explicit imports take precedence
+    let mut linked_imports = Imports::new();
+    for (namespace, instance) in &runtime.linked_instances {
+        linked_imports.register_namespace(
+            namespace,
+            instance
+                .exports
+                .iter()
+                .filter(|(name, _)| !ModuleIntrospection::is_internal(name))
+                .map(|(name, ext)| (name.clone(), ext.clone())),
+        );
+    }
+    linked_imports.extend(&import_object);
+    let mut import_object = linked_imports;
+
+    // Modules importing WASI get their own environment
+    let abis = WasiUtils::abis(module);
+    let wasi = match wasi {
+        Some(config) if !abis.is_empty() => {
+            if !config.env_type.supports(&abis) {
+                return Err(WasmerError::link(module_name, instance_name,
+                    "the module imports WASIX; create the WASI state with env_type = \"wasix\""));
             }
-        };
-        
-        // Add WASI imports if enabled
-        if let Some(wasi_env) = &runtime.wasi_env {
-            let wasi_imports = wasi_env.import_object(&mut runtime.store, module).unwrap_or_else(|_| imports! {});
-            // Merge imports - this is a bit tricky with the imports! macro structure
-            // For now, we'll just extend the import object if possible or create a new one
-            // Wasmer's ImportObject can be extended
-            import_object.extend(&wasi_imports);
+            Some(create_wasi_state(&mut runtime.store, config)?)
         }
+        _ => None,
+    };
+    if let Some(wasi) = &wasi {
+        let wasi_imports = wasi.env.import_object(&mut runtime.store, module).unwrap_or_else(|_| imports! {});
+        import_object.extend(&wasi_imports);
+    }
 
-        match Instance::new(&mut runtime.store, module, &import_object) {
-            Ok(final_instance) => {
-                env.as_mut(&mut runtime.store).instance = Some(final_instance.clone());
-                runtime.instances.insert(instance_name.clone(), final_instance.clone());
-                runtime.env = Some(env);
-                
-                // Initialize WASI if present
-                if let Some(wasi_env) = &runtime.wasi_env {
-                     // wasmer-wasix handles initialization automatically on first call usually, 
-                     // but we might need to call initialize explicitly if we want to be sure.
-                     // For now, let's assume it works as is.
-                     match wasi_env.clone().initialize(&mut runtime.store, final_instance.clone()) {
-                        Ok(_) => {},
-                        Err(e) => return format!("Error initializing WASI: {}", e),
-                     }
-                }
-                
-                format!("Instance '{}' created successfully", instance_name)
-            }
-            Err(e) => format!("Error creating instance: {}", e),
+    let instance = Instance::new(&mut runtime.store, module, &import_object)
+        .map_err(|e| instantiation_error(module_name, instance_name, e))?;
+
+    if let Some(wasi) = &wasi {
+        if let Err(e) = wasi.env.clone().initialize(&mut runtime.store, instance.clone()) {
+            return Err(WasmerError::link(module_name, instance_name, format!("WASI initialization failed: {}", e)));
         }
-    } else {
-        format!("Module '{}' not found", module_name)
     }
+    Ok((instance, wasi))
 }
 
-fn wasmer_call_function(runtime: &mut WasmerRuntime, instance_name: String, function_name: String, args: List) -> List {
-    if let Some(instance) = runtime.instances.get(&instance_name) {
-        if let Ok(func) = instance.exports.get_function(&function_name) {
-            let mut wasm_args = Vec::new();
-            for (_name, arg) in args.iter() {
-                match arg.rtype() {
-                    Rtype::Integers => {
-                        if let Some(val) = arg.as_integer() {
-                            wasm_args.push(Value::I32(val));
-                        }
-                    }
-                    Rtype::Doubles => {
-                        if let Some(val) = arg.as_real() {
-                            wasm_args.push(Value::F64(val));
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            match func.call(&mut runtime.store, &wasm_args) {
-                Ok(results) => {
-                    let result_list = List::from_names_and_values(
-                        ["success", "values"],
-                        [r!(true), convert_wasm_values_to_r(Box::<[Value]>::from(results))],
-                    ).unwrap();
-                    result_list
-                }
-                Err(e) => {
-                    List::from_names_and_values(
-                        ["success", "error"],
-                        [r!(false), r!(format!("Error calling function: {}", e))],
-                    ).unwrap()
-                }
-            }
-        } else {
-            List::from_names_and_values(
-                ["success", "error"],
-                [r!(false), r!(format!("Function '{}' not found", function_name))],
-            ).unwrap()
+fn wasmer_instantiate(runtime: &mut WasmerRuntime, module_name: String, instance_name: String, globals: Vec<(String, Global)>) -> WasmerResult<String> {
+    let env = FunctionEnv::new(&mut runtime.store, WasmerEnv { instance: None });
+    let mut import_object = imports! {
+        "env" => {
+            "r_host_call" => create_generic_r_host_function(&env, &mut runtime.store),
         }
-    } else {
-        List::from_names_and_values(
-            ["success", "error"],
-            [r!(false), r!(format!("Instance '{}' not found", instance_name))],
-        ).unwrap()
+    };
+    for (name, global) in globals {
+        import_object.define("env", &name, global);
     }
+
+    let instance = instantiate_module(runtime, &module_name, &instance_name, import_object)?;
+    env.as_mut(&mut runtime.store).instance = Some(instance);
+    runtime.env = Some(env);
+    Ok(format!("Instance '{}' created successfully", instance_name))
+}
+
+fn wasmer_call_function(runtime: &mut WasmerRuntime, instance_name: String, function_name: String, args: List) -> WasmerResult<Robj> {
+    let (instance, func) = lookup_function(&runtime.instances, &instance_name, &function_name)?;
+    guarded_call(runtime, &instance, |store, int64_mode| {
+        call_function(store, &func, &instance_name, &function_name, &args, int64_mode)
+    })
+}
+
+/// Look up an exported function and the instance exporting it
+fn lookup_function(instances: &HashMap<String, Instance>, instance_name: &str, function_name: &str) -> WasmerResult<(Instance, Function)> {
+    let instance = lookup_instance(instances, instance_name)?;
+    let func = instance
+        .exports
+        .get_function(function_name)
+        .map_err(|_| WasmerError::export_not_found("function", function_name, instance_name))?
+        .clone();
+    Ok((instance.clone(), func))
 }
 
-fn wasmer_list_exports(runtime: &mut WasmerRuntime, instance_name: String) -> List {
-    if let Some(instance) = runtime.instances.get(&instance_name) {
-        let mut exports = Vec::new();
-        for (name, _) in instance.exports.iter() {
-            exports.push(name.clone());
+/// Run a call into `instance` under the runtime's limits: with per-call fuel the instance is
+/// refilled first, a watchdog stops the call on timeout or user interrupt, and the resulting traps
+/// become `wasmer_timeout`, `wasmer_interrupt` or `wasmer_out_of_fuel`. The `wasmer.int64` option is
+/// read once here and passed to `call`
+fn guarded_call<T>(
+    runtime: &mut WasmerRuntime,
+    instance: &Instance,
+    call: impl FnOnce(&mut Store, Int64Mode) -> WasmerResult<T>,
+) -> WasmerResult<T> {
+    let metered = runtime.options.fuel.is_some() && FuelMeter::is_metered(instance);
+    if let (true, true, Some(fuel)) = (metered, runtime.options.fuel_per_call, runtime.options.fuel) {
+        FuelMeter::refill(&mut runtime.store, instance, fuel).map_err(WasmerError::runtime)?;
+    }
+    let watchdog = CallWatchdog::start(&mut runtime.store, instance, runtime.options.timeout);
+    let result = Int64Mode::during(|int64_mode| call(&mut runtime.store, int64_mode));
+    let stopped = watchdog.and_then(|watchdog| watchdog.finish(&mut runtime.store));
+    match (result, stopped) {
+        (Err(error @ WasmerError::Trap { .. }), Some(StopReason::Timeout(limit))) => Err(error.with_trap_kind(
+            "wasmer_timeout",
+            "timeout",
+            format!("call timed out after {} seconds", limit.as_secs_f64()),
+        )),
+        (Err(error @ WasmerError::Trap { .. }), Some(StopReason::Interrupt)) => {
+            Err(error.with_trap_kind("wasmer_interrupt", "interrupted", "call interrupted by the user"))
         }
-        List::from_names_and_values(
-            ["success", "exports"],
-            [r!(true), r!(exports)],
-        ).unwrap()
-    } else {
-        List::from_names_and_values(
-            ["success", "error"],
-            [r!(false), r!(format!("Instance '{}' not found", instance_name))],
-        ).unwrap()
+        (Err(error @ WasmerError::Trap { .. }), _) if metered && FuelMeter::is_exhausted(&mut runtime.store, instance) => {
+            Err(error.with_trap_kind("wasmer_out_of_fuel", "out_of_fuel", "out of fuel"))
+        }
+        (result, _) => result,
     }
 }
 
+/// Call a resolved function with R arguments checked against its signature
+fn call_function(
+    store: &mut Store,
+    func: &Function,
+    instance_name: &str,
+    function_name: &str,
+    args: &List,
+    int64_mode: Int64Mode,
+) -> WasmerResult<Robj> {
+    // Check arity and coerce each argument to the parameter type declared by the function
+    let param_types = func.ty(store).params().to_vec();
+    let wasm_args = TypeConverter::validate_args(args, &param_types).map_err(|e| WasmerError::Argument {
+        function: Some(function_name.to_string()),
+        message: e,
+    })?;
+    let results = func
+        .call(store, &wasm_args)
+        .map_err(|e| WasmerError::trap(instance_name, function_name, e))?;
+    Ok(TypeConverter::wasm_vector_to_r(&results, int64_mode))
+}
+
+fn wasmer_list_exports(runtime: &mut WasmerRuntime, instance_name: String) -> WasmerResult<Vec<String>> {
+    let instance = lookup_instance(&runtime.instances, &instance_name)?;
+    Ok(instance
+        .exports
+        .iter()
+        .filter(|(name, _)| !ModuleIntrospection::is_internal(name))
+        .map(|(name, _)| name.clone())
+        .collect())
+}
+
 /// List exported function signatures (name, input types, output types) for a WASM instance
 ///
 /// @title List WASM function signatures
 /// @description List exported function signatures (name, input types, output types) for a WASM instance.
 /// @family exports and signatures
-/// @seealso [wasmer_list_exports_ext()]
+/// @seealso [wasmer_list_exports_ext()], [wasmer_module_exports_ext()]
 /// @param ptr External pointer to WasmerRuntime.
 /// @param instance_name Name of the instance.
 /// @return Data frame with columns: name, params, results
@@ -355,30 +604,130 @@ fn wasmer_list_exports(runtime: &mut WasmerRuntime, instance_name: String) -> Li
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_list_function_signatures_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String) -> List {
+pub fn wasmer_list_function_signatures_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String) -> WasmerResult<List> {
     let runtime = ptr.as_mut();
-    if let Some(instance) = runtime.instances.get(&instance_name) {
-        let module = instance.module();
-        let mut names = Vec::new();
-        let mut params = Vec::new();
-        let mut results = Vec::new();
-        for export in module.exports() {
-            if let wasmer::ExternType::Function(func_ty) = export.ty() {
-                names.push(export.name().to_string());
-                params.push(format!("{:?}", func_ty.params()));
-                results.push(format!("{:?}", func_ty.results()));
-            }
+    let instance = lookup_instance(&runtime.instances, &instance_name)?;
+    let module = instance.module();
+    let mut names = Vec::new();
+    let mut params = Vec::new();
+    let mut results = Vec::new();
+    for export in module.exports() {
+        if let wasmer::ExternType::Function(func_ty) = export.ty() {
+            names.push(export.name().to_string());
+            params.push(format!("{:?}", func_ty.params()));
+            results.push(format!("{:?}", func_ty.results()));
         }
-        List::from_names_and_values(
-            ["name", "params", "results"],
-            [r!(names), r!(params), r!(results)]
-        ).unwrap()
-    } else {
-        List::from_names_and_values(
-            ["error"],
-            [r!(format!("Instance '{}' not found", instance_name))]
-        ).unwrap()
     }
+    Ok(List::from_names_and_values(
+        ["name", "params", "results"],
+        [r!(names), r!(params), r!(results)]
+    ).unwrap())
+}
+
+/// List the imports of a compiled module
+///
+/// @title List module imports
+/// @description List what a module needs to be instantiated, without instantiating it.
+///
+/// Besides the import's `module` and `name`, the data frame has the columns:
+/// * `kind`: `"function"`, `"global"`, `"memory"`, `"table"` or `"tag"`.
+/// * `type`: the type as printed for module handles, e.g. `"func (i32, i32) -> i32"`.
+/// * `params`, `results`: list columns with the value types of functions, empty otherwise.
+/// * `value_type`: value type of globals and element type of tables.
+/// * `mutable`: whether a global is mutable.
+/// * `minimum`, `maximum`: size limits of memories (in pages) and tables (in elements); `maximum`
+///   is `NA` when unbounded.
+/// * `shared`: whether a memory is shared.
+///
+/// Columns that do not apply to an import's kind are `NA`.
+/// @family exports and signatures
+/// @seealso [wasmer_module_exports_ext()], [wasmer_module_custom_sections_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param module_name Name of the module.
+/// @return Data frame with one row per import
+/// @examples
+/// \dontrun{
+/// imports <- wasmer_module_imports_ext(ptr, "mod1")
+/// imports[imports$module == "env" & imports$kind == "function", c("name", "type")]
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_module_imports_ext(mut ptr: ExternalPtr<WasmerRuntime>, module_name: String) -> WasmerResult<Robj> {
+    let runtime = ptr.as_mut();
+    let module = lookup_module(&runtime.modules, &module_name)?;
+    Ok(ModuleIntrospection::imports(module))
+}
+
+/// List the exports of a compiled module
+///
+/// @title List module exports
+/// @description List what a module exports, without instantiating it. The data frame has a
+/// `name` column followed by the type columns described in [wasmer_module_imports_ext()].
+/// @family exports and signatures
+/// @seealso [wasmer_module_imports_ext()], [wasmer_list_function_signatures_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param module_name Name of the module.
+/// @return Data frame with one row per export
+/// @examples
+/// \dontrun{
+/// exports <- wasmer_module_exports_ext(ptr, "mod1")
+/// exports$params[exports$name == "add"]
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_module_exports_ext(mut ptr: ExternalPtr<WasmerRuntime>, module_name: String) -> WasmerResult<Robj> {
+    let runtime = ptr.as_mut();
+    let module = lookup_module(&runtime.modules, &module_name)?;
+    Ok(ModuleIntrospection::exports(module))
+}
+
+/// Read custom sections of a compiled module
+///
+/// @title Read module custom sections
+/// @description Return the contents of the custom sections with the given name, such as
+/// `"name"`, `"producers"` or tool-specific metadata. A module may contain several sections of
+/// the same name.
+/// @family exports and signatures
+/// @seealso [wasmer_module_imports_ext()], [wasmer_module_exports_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param module_name Name of the module.
+/// @param section_name Name of the custom section.
+/// @return List of raw vectors, in module order; empty when there is no such section
+/// @examples
+/// \dontrun{
+/// producers <- wasmer_module_custom_sections_ext(ptr, "mod1", "producers")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_module_custom_sections_ext(mut ptr: ExternalPtr<WasmerRuntime>, module_name: String, section_name: String) -> WasmerResult<List> {
+    let runtime = ptr.as_mut();
+    let module = lookup_module(&runtime.modules, &module_name)?;
+    Ok(ModuleIntrospection::custom_sections(module, &section_name))
+}
+
+/// List the WASI ABIs a module imports
+///
+/// @title List module WASI ABIs
+/// @description Tell which system interfaces a module was compiled against, from the namespaces
+/// of its imports: `"wasi_unstable"`, `"wasi_snapshot_preview1"`, `"wasix_32v1"` or
+/// `"wasix_64v1"`. A module importing a `wasix_*` ABI needs a WASI state created with
+/// `env_type = "wasix"`.
+/// @family exports and signatures
+/// @seealso [wasmer_module_imports_ext()], [wasmer_wasi_state_new_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param module_name Name of the module.
+/// @return Character vector of ABI names, empty when the module does not use WASI
+/// @examples
+/// \dontrun{
+/// env_type <- if (any(startsWith(wasmer_module_wasi_abi_ext(ptr, "prog"), "wasix"))) "wasix" else "wasi"
+/// wasmer_wasi_run_ext(ptr, "prog", env_type = env_type)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_module_wasi_abi_ext(mut ptr: ExternalPtr<WasmerRuntime>, module_name: String) -> WasmerResult<Vec<String>> {
+    let runtime = ptr.as_mut();
+    let module = lookup_module(&runtime.modules, &module_name)?;
+    Ok(WasiUtils::abis(module).iter().map(|abi| abi.get_namespace_str().to_string()).collect())
 }
 
 /// Create a simple "Hello World" example
@@ -395,37 +744,27 @@ pub fn wasmer_list_function_signatures_ext(mut ptr: ExternalPtr<WasmerRuntime>,
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_hello_world_example_ext(mut ptr: ExternalPtr<WasmerRuntime>) -> String {
+pub fn wasmer_hello_world_example_ext(mut ptr: ExternalPtr<WasmerRuntime>) -> WasmerResult<String> {
     let runtime = ptr.as_mut();
     let wat_code = r#"
 (module
   (func $hello (export "hello") (result i32)
     i32.const 42)
 )"#;
-    match wat2wasm(wat_code.as_bytes()) {
-        Ok(wasm_bytes) => {
-            match Module::new(&runtime.store, wasm_bytes) {
-                Ok(module) => {
-                    let import_object = imports! {};
-                    match Instance::new(&mut runtime.store, &module, &import_object) {
-                        Ok(instance) => {
-                            if let Ok(hello_func) = instance.exports.get_typed_function::<(), i32>(&runtime.store, "hello") {
-                                match hello_func.call(&mut runtime.store) {
-                                    Ok(result) => format!("Hello World! Function returned: {}", result),
-                                    Err(e) => format!("Error calling function: {}", e),
-                                }
-                            } else {
-                                "Could not get hello function".to_string()
-                            }
-                        }
-                        Err(e) => format!("Error creating instance: {}", e),
-                    }
-                }
-                Err(e) => format!("Error compiling module: {}", e),
-            }
-        }
-        Err(e) => format!("Error converting WAT: {}", e),
-    }
+    let wasm_bytes = wat2wasm(wat_code.as_bytes())
+        .map_err(|e| WasmerError::compile("hello", e))?;
+    let module = Module::new(&runtime.store, wasm_bytes)
+        .map_err(|e| WasmerError::compile("hello", e))?;
+    let instance = Instance::new(&mut runtime.store, &module, &imports! {})
+        .map_err(|e| instantiation_error("hello", "hello", e))?;
+    let hello_func = instance
+        .exports
+        .get_typed_function::<(), i32>(&runtime.store, "hello")
+        .map_err(|_| WasmerError::export_not_found("function", "hello", "hello"))?;
+    let result = hello_func
+        .call(&mut runtime.store)
+        .map_err(|e| WasmerError::trap("hello", "hello", e))?;
+    Ok(format!("Hello World! Function returned: {}", result))
 }
 
 /// Math operations example
@@ -444,7 +783,7 @@ pub fn wasmer_hello_world_example_ext(mut ptr: ExternalPtr<WasmerRuntime>) -> St
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_math_example_ext(mut ptr: ExternalPtr<WasmerRuntime>, a: i32, b: i32) -> List {
+pub fn wasmer_math_example_ext(mut ptr: ExternalPtr<WasmerRuntime>, a: i32, b: i32) -> WasmerResult<List> {
     let runtime = ptr.as_mut();
     let wat_code = r#"
 (module
@@ -457,42 +796,26 @@ pub fn wasmer_math_example_ext(mut ptr: ExternalPtr<WasmerRuntime>, a: i32, b: i
     local.get $y
     i32.mul)
 )"#;
-    match wat2wasm(wat_code.as_bytes()) {
-        Ok(wasm_bytes) => {
-            match Module::new(&runtime.store, wasm_bytes) {
-                Ok(module) => {
-                    let import_object = imports! {};
-                    match Instance::new(&mut runtime.store, &module, &import_object) {
-                        Ok(instance) => {
-                            let mut results = Vec::new();
-                            if let Ok(add_func) = instance.exports.get_typed_function::<(i32, i32), i32>(&runtime.store, "add") {
-                                if let Ok(result) = add_func.call(&mut runtime.store, a, b) {
-                                    results.push(("add", result));
-                                }
-                            }
-                            if let Ok(mul_func) = instance.exports.get_typed_function::<(i32, i32), i32>(&runtime.store, "multiply") {
-                                if let Ok(result) = mul_func.call(&mut runtime.store, a, b) {
-                                    results.push(("multiply", result));
-                                }
-                            }
-                            let names: Vec<&str> = results.iter().map(|(name, _)| *name).collect();
-                            let values: Vec<i32> = results.iter().map(|(_, value)| *value).collect();
-                            List::from_names_and_values(names, values.iter().map(|&v| r!(v))).unwrap()
-                        }
-                        Err(e) => {
-                            List::from_names_and_values(["error"], [r!(format!("Error creating instance: {}", e))]).unwrap()
-                        }
-                    }
-                }
-                Err(e) => {
-                    List::from_names_and_values(["error"], [r!(format!("Error compiling module: {}", e))]).unwrap()
-                }
-            }
-        }
-        Err(e) => {
-            List::from_names_and_values(["error"], [r!(format!("Error converting WAT: {}", e))]).unwrap()
-        }
+    let wasm_bytes = wat2wasm(wat_code.as_bytes())
+        .map_err(|e| WasmerError::compile("math", e))?;
+    let module = Module::new(&runtime.store, wasm_bytes)
+        .map_err(|e| WasmerError::compile("math", e))?;
+    let instance = Instance::new(&mut runtime.store, &module, &imports! {})
+        .map_err(|e| instantiation_error("math", "math", e))?;
+    let mut results = Vec::new();
+    for name in ["add", "multiply"] {
+        let func = instance
+            .exports
+            .get_typed_function::<(i32, i32), i32>(&runtime.store, name)
+            .map_err(|_| WasmerError::export_not_found("function", name, "math"))?;
+        let result = func
+            .call(&mut runtime.store, a, b)
+            .map_err(|e| WasmerError::trap("math", name, e))?;
+        results.push((name, result));
     }
+    let names: Vec<&str> = results.iter().map(|(name, _)| *name).collect();
+    let values: Vec<i32> = results.iter().map(|(_, value)| *value).collect();
+    Ok(List::from_names_and_values(names, values.iter().map(|&v| r!(v))).unwrap())
 }
 
 /// Create an instance with host functions for mathematical operations
@@ -511,30 +834,21 @@ pub fn wasmer_math_example_ext(mut ptr: ExternalPtr<WasmerRuntime>, a: i32, b: i
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_instantiate_with_math_imports_ext(mut ptr: ExternalPtr<WasmerRuntime>, module_name: String, instance_name: String) -> String {
+pub fn wasmer_instantiate_with_math_imports_ext(mut ptr: ExternalPtr<WasmerRuntime>, module_name: String, instance_name: String) -> WasmerResult<String> {
     let runtime = ptr.as_mut();
-    if let Some(module) = runtime.modules.get(&module_name) {
-        let math_functions = WasmerHostFunctions::create_math_functions(&mut runtime.store);
-        let import_object = imports! {
-            "env" => {
-                "square" => math_functions.get("square").unwrap().clone(),
-                "cube" => math_functions.get("cube").unwrap().clone(),
-                "factorial" => math_functions.get("factorial").unwrap().clone(),
-                "log" => WasmerHostFunctions::create_log_function(&mut runtime.store),
-                "timestamp" => WasmerHostFunctions::create_timestamp_function(&mut runtime.store),
-                "random" => WasmerHostFunctions::create_random_function(&mut runtime.store),
-            }
-        };
-        match Instance::new(&mut runtime.store, module, &import_object) {
-            Ok(instance) => {
-                runtime.instances.insert(instance_name.clone(), instance);
-                format!("Instance '{}' created with math imports", instance_name)
-            }
-            Err(e) => format!("Error creating instance: {}", e),
+    let math_functions = WasmerHostFunctions::create_math_functions(&mut runtime.store);
+    let import_object = imports! {
+        "env" => {
+            "square" => math_functions.get("square").unwrap().clone(),
+            "cube" => math_functions.get("cube").unwrap().clone(),
+            "factorial" => math_functions.get("factorial").unwrap().clone(),
+            "log" => WasmerHostFunctions::create_log_function(&mut runtime.store),
+            "timestamp" => WasmerHostFunctions::create_timestamp_function(&mut runtime.store),
+            "random" => WasmerHostFunctions::create_random_function(&mut runtime.store),
         }
-    } else {
-        format!("Module '{}' not found", module_name)
-    }
+    };
+    instantiate_module(runtime, &module_name, &instance_name, import_object)?;
+    Ok(format!("Instance '{}' created with math imports", instance_name))
 }
 
 /// Advanced function calling with type safety
@@ -547,54 +861,16 @@ pub fn wasmer_instantiate_with_math_imports_ext(mut ptr: ExternalPtr<WasmerRunti
 /// @param instance_name String name of the instance.
 /// @param function_name String name of the function to call.
 /// @param args List of arguments with proper type conversion.
-/// @return List with success flag and result or error
+/// @return The function result: a single value, or a list for multiple results
 /// @examples
 /// \dontrun{
 /// wasmer_call_function_safe_ext(ptr, "inst1", "add", list(1, 2))
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_call_function_safe_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, function_name: String, args: List) -> List {
+pub fn wasmer_call_function_safe_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, function_name: String, args: List) -> WasmerResult<Robj> {
     let runtime = ptr.as_mut();
-    if let Some(instance) = runtime.instances.get(&instance_name) {
-        if let Ok(func) = instance.exports.get_function(&function_name) {
-            // Convert R arguments to Wasm values using the type converter
-            match TypeConverter::r_vector_to_wasm(args) {
-                Ok(wasm_args) => {
-                    match func.call(&mut runtime.store, &wasm_args) {
-                        Ok(results) => {
-                            List::from_names_and_values(
-                                ["success", "values"],
-                                [r!(true), TypeConverter::wasm_vector_to_r(&results)],
-                            ).unwrap()
-                        }
-                        Err(e) => {
-                            List::from_names_and_values(
-                                ["success", "error"],
-                                [r!(false), r!(format!("Error calling function: {}", e))],
-                            ).unwrap()
-                        }
-                    }
-                }
-                Err(e) => {
-                    List::from_names_and_values(
-                        ["success", "error"],
-                        [r!(false), r!(format!("Type conversion error: {}", e))],
-                    ).unwrap()
-                }
-            }
-        } else {
-            List::from_names_and_values(
-                ["success", "error"],
-                [r!(false), r!(format!("Function '{}' not found", function_name))],
-            ).unwrap()
-        }
-    } else {
-        List::from_names_and_values(
-            ["success", "error"],
-            [r!(false), r!(format!("Instance '{}' not found", instance_name))],
-        ).unwrap()
-    }
+    wasmer_call_function(runtime, instance_name, function_name, args)
 }
 
 /// Example with host function imports
@@ -611,7 +887,7 @@ pub fn wasmer_call_function_safe_ext(mut ptr: ExternalPtr<WasmerRuntime>, instan
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_host_function_example_ext(mut ptr: ExternalPtr<WasmerRuntime>) -> List {
+pub fn wasmer_host_function_example_ext(mut ptr: ExternalPtr<WasmerRuntime>) -> WasmerResult<List> {
     let runtime = ptr.as_mut();
     let wat_code = r#"
 (module
@@ -626,63 +902,44 @@ pub fn wasmer_host_function_example_ext(mut ptr: ExternalPtr<WasmerRuntime>) ->
     (call $timestamp)
   )
 )"#;
-    match wat2wasm(wat_code.as_bytes()) {
-        Ok(wasm_bytes) => {
-            match Module::new(&runtime.store, wasm_bytes) {
-                Ok(module) => {
-                    let import_object = imports! {
-                        "env" => {
-                            "square" => Function::new_typed(&mut runtime.store, |x: i32| -> i32 { x * x }),
-                            "log" => Function::new_typed(&mut runtime.store, |x: i32| {
-                                rprintln!("WASM logged: {}", x);
-                            }),
-                            "timestamp" => Function::new_typed(&mut runtime.store, || -> i64 {
-                                std::time::SystemTime::now()
-                                    .duration_since(std::time::UNIX_EPOCH)
-                                    .unwrap()
-                                    .as_secs() as i64
-                            }),
-                        }
-                    };
-                    match Instance::new(&mut runtime.store, &module, &import_object) {
-                        Ok(instance) => {
-                            let mut results = std::collections::HashMap::new();
-                            if let Ok(test_func) = instance.exports.get_typed_function::<i32, i32>(&runtime.store, "test") {
-                                if let Ok(result) = test_func.call(&mut runtime.store, 5) {
-                                    results.insert("test_result".to_string(), r!(result));
-                                }
-                            }
-                            if let Ok(time_func) = instance.exports.get_typed_function::<(), i64>(&runtime.store, "get_time") {
-                                if let Ok(timestamp) = time_func.call(&mut runtime.store) {
-                                    results.insert("timestamp".to_string(), r!(timestamp as f64));
-                                }
-                            }
-                            List::from_names_and_values(
-                                ["success", "results"],
-                                [r!(true), {
-                                    let names: Vec<String> = results.keys().cloned().collect();
-                                    let values: Vec<Robj> = results.values().cloned().collect();
-                                    List::from_names_and_values(names.iter().map(|s| s.as_str()).collect::<Vec<_>>(), values).unwrap().into()
-                                }],
-                            ).unwrap()
-                        }
-                        Err(e) => {
-                            List::from_names_and_values(
-                                ["success", "error"],
-                                [r!(false), r!(format!("Error creating instance: {}", e))],
-                            ).unwrap()
-                        }
-                    }
-                }
-                Err(e) => {
-                    List::from_names_and_values(["success", "error"], [r!(false), r!(format!("Error compiling module: {}", e))]).unwrap()
-                }
-            }
-        }
-        Err(e) => {
-            List::from_names_and_values(["success", "error"], [r!(false), r!(format!("Error converting WAT: {}", e))]).unwrap()
+    let wasm_bytes = wat2wasm(wat_code.as_bytes())
+        .map_err(|e| WasmerError::compile("host_example", e))?;
+    let module = Module::new(&runtime.store, wasm_bytes)
+        .map_err(|e| WasmerError::compile("host_example", e))?;
+    let import_object = imports! {
+        "env" => {
+            "square" => Function::new_typed(&mut runtime.store, |x: i32| -> i32 { x * x }),
+            "log" => Function::new_typed(&mut runtime.store, |x: i32| {
+                rprintln!("WASM logged: {}", x);
+            }),
+            "timestamp" => Function::new_typed(&mut runtime.store, || -> i64 {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs() as i64
+            }),
         }
-    }
+    };
+    let instance = Instance::new(&mut runtime.store, &module, &import_object)
+        .map_err(|e| instantiation_error("host_example", "host_example", e))?;
+    let test_func = instance
+        .exports
+        .get_typed_function::<i32, i32>(&runtime.store, "test")
+        .map_err(|_| WasmerError::export_not_found("function", "test", "host_example"))?;
+    let test_result = test_func
+        .call(&mut runtime.store, 5)
+        .map_err(|e| WasmerError::trap("host_example", "test", e))?;
+    let time_func = instance
+        .exports
+        .get_typed_function::<(), i64>(&runtime.store, "get_time")
+        .map_err(|_| WasmerError::export_not_found("function", "get_time", "host_example"))?;
+    let timestamp = time_func
+        .call(&mut runtime.store)
+        .map_err(|e| WasmerError::trap("host_example", "get_time", e))?;
+    Ok(List::from_names_and_values(
+        ["test_result", "timestamp"],
+        [r!(test_result), r!(timestamp as f64)],
+    ).unwrap())
 }
 
 /// Create a new Wasmer runtime
@@ -690,7 +947,7 @@ pub fn wasmer_host_function_example_ext(mut ptr: ExternalPtr<WasmerRuntime>) ->
 /// @title Create a new Wasmer runtime
 /// @description Create a new Wasmer runtime for executing WebAssembly modules.
 /// @family runtime management
-/// @seealso [wasmer_runtime_new_with_compiler_ext()], [wasmer_runtime_release_ressources()]
+/// @seealso [wasmer_runtime_new_with_compiler_ext()], [wasmer_runtime_new_headless_ext()], [wasmer_runtime_release_ressources()]
 /// @return External pointer to WasmerRuntime
 /// @examples
 /// \dontrun{
@@ -706,177 +963,945 @@ pub fn wasmer_runtime_new() -> ExternalPtr<WasmerRuntime> {
 ///
 /// @title Create a new Wasmer runtime with a specific compiler
 /// @description Create a new Wasmer runtime for executing WebAssembly modules using a specified compiler backend.
+///
+/// `options` is a named list configuring the engine:
+/// * `fuel`: fuel each instance starts with. Every executed wasm instruction costs one unit and a
+///   call that runs out of fuel raises a `wasmer_out_of_fuel` condition (a `wasmer_trap`). Use
+///   [wasmer_fuel_remaining_ext()] and [wasmer_fuel_set_ext()] to query and refill an instance.
+/// * `fuel_per_call`: if `TRUE`, refill the fuel to `fuel` before every call, so that `fuel`
+///   bounds each call rather than the lifetime of each instance.
+/// * `timeout`: wall-clock limit of each call in seconds. A call running longer raises a
+///   `wasmer_timeout` condition (a `wasmer_trap`); change it later with [wasmer_runtime_set_timeout_ext()].
+/// * `interruptible`: if `TRUE` (the default), compiled code checks for interrupts at every
+///   function entry and loop iteration, and every call is watched by a separate thread, so that
+///   timeouts and user interrupts (Ctrl-C, or Esc in RStudio) stop a running call with a
+///   `wasmer_timeout` or `wasmer_interrupt` condition. Set it to `FALSE` for maximum speed, as
+///   watching adds to the cost of every call; such calls cannot time out or be interrupted.
+/// * `max_memory_pages`: largest size of any memory, in 64 KiB pages. Memories are created with
+///   their maximum lowered to this limit, so that `memory.grow` past it returns -1 in wasm and
+///   [wasmer_memory_grow_ext()] raises a `wasmer_limit_error` condition. Instantiating a module
+///   whose memory starts larger fails with a `wasmer_link_error`.
+/// * `max_table_elements`: the same limit for the number of table elements.
+/// * `stack_size`: stack size in bytes available to wasm code; deeper calls trap with
+///   `trap_code` `"stack_overflow"`.
+/// * `features`: named list of WebAssembly proposals to enable (`TRUE`) or disable (`FALSE`) on
+///   top of the compiler's defaults: `simd`, `threads`, `bulk_memory`, `reference_types`,
+///   `multi_value`, `tail_call`, `exceptions`, `memory64` and `relaxed_simd`. Enabling a proposal
+///   the compiler does not support is an error; see [wasmer_compiler_features_ext()]. Modules
+///   using a disabled proposal fail to compile with a `wasmer_compile_error`.
+/// * `target`: target triple to compile for, e.g. `"aarch64-unknown-linux-gnu"`, instead of
+///   this machine. Modules compiled for another machine cannot be instantiated, only serialized
+///   with [wasmer_module_serialize_ext()].
+/// * `cpu_features`: character vector adjusting the CPU features the generated code may use, e.g.
+///   `"-avx2"` to avoid AVX2 for reproducible floating point or older nodes, or `"sse4.2"` to
+///   require SSE 4.2. The host starts from the features it detects and other targets from none.
+///   See [wasmer_runtime_target_ext()].
+///
+/// The store remains usable after a call is stopped. Time spent in R host functions is counted
+/// but not interrupted: the call stops when the host function returns to wasm.
+///
+/// Modules compiled with different options are not interchangeable: serialized artifacts record
+/// the options and are only loaded by runtimes created with the same ones.
 /// @family runtime management
 /// @seealso [wasmer_runtime_new()], [wasmer_runtime_release_ressources()]
 /// @param compiler_name Name of the compiler ("cranelift", "singlepass").
+/// @param options Named list of engine options, or NULL for the defaults.
 /// @return External pointer to WasmerRuntime
 /// @examples
 /// \dontrun{
 /// ptr <- wasmer_runtime_new_with_compiler_ext("cranelift")
+/// ptr <- wasmer_runtime_new_with_compiler_ext("singlepass", list(fuel = 1e6, fuel_per_call = TRUE))
+/// ptr <- wasmer_runtime_new_with_compiler_ext("cranelift", list(timeout = 5))
+/// ptr <- wasmer_runtime_new_with_compiler_ext("cranelift", list(max_memory_pages = 256, stack_size = 2^20))
+/// ptr <- wasmer_runtime_new_with_compiler_ext("cranelift", list(features = list(simd = FALSE, threads = FALSE)))
+/// ptr <- wasmer_runtime_new_with_compiler_ext("cranelift", list(cpu_features = c("-avx2", "-avx512f")))
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_runtime_new_with_compiler_ext(compiler_name: String) -> ExternalPtr<WasmerRuntime> {
-    let compiler_config = match CompilerUtils::get_compiler_config(&compiler_name) {
-        Ok(c) => c,
-        Err(e) => {
-            rprintln!("Error getting compiler config: {}", e);
-            return ExternalPtr::new(WasmerRuntime::new()); // Fallback to default
-        }
+pub fn wasmer_runtime_new_with_compiler_ext(compiler_name: String, #[default = "NULL"] options: Nullable<List>) -> WasmerResult<ExternalPtr<WasmerRuntime>> {
+    let options = match options {
+        NotNull(options) => RuntimeOptions::from_list(&options).map_err(WasmerError::argument)?,
+        Null => RuntimeOptions::default(),
     };
-    // Note: WasmerRuntime::new() uses Store::default(). We need a way to pass the compiler.
-    // We'll modify WasmerRuntime::new to accept an optional compiler config or create a new constructor.
-    // Since WasmerRuntime struct definition is simple, we can just create it here.
-    let engine = EngineBuilder::new(compiler_config).engine();
-    let store = Store::new(engine);
-    let runtime = WasmerRuntime {
-        store,
-        modules: HashMap::new(),
-        instances: HashMap::new(),
-        r_function_registry: HashMap::new(),
-        env: None,
-        memory_manager: WasmerMemoryManager::new(),
-        wasi_env: None,
-        shutdown: false,
+    let runtime = WasmerRuntime::build(Some(&compiler_name), options)
+        .map_err(WasmerError::argument)?;
+    Ok(ExternalPtr::new(runtime))
+}
+
+/// Create a headless Wasmer runtime
+///
+/// @title Create a headless Wasmer runtime
+/// @description Create a runtime without a compiler. It starts faster and carries no code
+/// generator, but can only load modules precompiled by [wasmer_module_serialize_ext()] with
+/// [wasmer_module_deserialize_ext()]; [wasmer_compile_wat_ext()] and [wasmer_compile_wasm_ext()]
+/// raise a `wasmer_compile_error` condition.
+///
+/// Artifacts of any compiler can be loaded, but modules cannot be serialized again.
+/// `options` takes the same entries as
+/// [wasmer_runtime_new_with_compiler_ext()] and must match the options the artifacts were compiled
+/// with, e.g. `fuel` for metered modules; the limits and `timeout` apply as usual.
+/// @family runtime management
+/// @seealso [wasmer_runtime_new_with_compiler_ext()], [wasmer_module_deserialize_ext()]
+/// @param options Named list of engine options, or NULL for the defaults.
+/// @return External pointer to WasmerRuntime
+/// @examples
+/// \dontrun{
+/// ptr <- wasmer_runtime_new_headless_ext()
+/// wasmer_module_deserialize_ext(ptr, "mod1.wasmu", "mod1")
+/// ptr <- wasmer_runtime_new_headless_ext(list(fuel = 1e6, timeout = 5))
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_runtime_new_headless_ext(#[default = "NULL"] options: Nullable<List>) -> WasmerResult<ExternalPtr<WasmerRuntime>> {
+    let options = match options {
+        NotNull(options) => RuntimeOptions::from_list(&options).map_err(WasmerError::argument)?,
+        Null => RuntimeOptions::default(),
     };
-    ExternalPtr::new(runtime)
+    let runtime = WasmerRuntime::build(None, options)
+        .map_err(WasmerError::argument)?;
+    Ok(ExternalPtr::new(runtime))
 }
 
-/// Create a WASI or WASIX state for the runtime
+/// Set the call timeout of a runtime
 ///
-/// @title Create WASI/WASIX state
-/// @description Create a WASI or WASIX state for the runtime.
-/// @family module instantiation
-/// @seealso [wasmer_instantiate_ext()], [wasmer_instantiate_with_math_imports_ext()], [wasmer_instantiate_with_table_ext()]
+/// @title Set the call timeout
+/// @description Set the wall-clock limit applied to every subsequent call into the runtime's
+/// instances. A call running longer is stopped with a `wasmer_timeout` condition and the store
+/// remains usable. Runtimes created with `interruptible = FALSE` cannot time out calls.
+/// @family runtime management
+/// @seealso [wasmer_runtime_new_with_compiler_ext()]
 /// @param ptr External pointer to WasmerRuntime.
-/// @param module_name Name of the module (for WASI/WASIX args).
-/// @param env_type Environment type: "wasi" (default) or "wasix".
-/// @return TRUE if successful, FALSE otherwise
+/// @param timeout Limit in seconds, or NULL to remove it.
+/// @return TRUE
 /// @examples
 /// \dontrun{
-/// wasmer_wasi_state_new_ext(ptr, "mod1", "wasi")
+/// wasmer_runtime_set_timeout_ext(ptr, 2)
+/// result <- tryCatch(wasmer_call_function_ext(ptr, "inst", "run", list()),
+///                    wasmer_timeout = function(e) NULL)
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_wasi_state_new_ext(mut ptr: ExternalPtr<WasmerRuntime>, module_name: String, env_type: Option<String>) -> bool {
+pub fn wasmer_runtime_set_timeout_ext(mut ptr: ExternalPtr<WasmerRuntime>, timeout: Robj) -> WasmerResult<bool> {
     let runtime = ptr.as_mut();
-    let _guard = TOKIO_RUNTIME.enter();
-    let env_type = env_type.unwrap_or_else(|| "wasi".to_string());
-    match env_type.as_str() {
-        "wasix" => {
-            match WasiUtils::create_wasi_env(&mut runtime.store, &module_name) {
-                Ok(env) => {
-                    runtime.wasi_env = Some(env);
-                    true
-                }
-                Err(e) => {
-                    rprintln!("Error creating WASIX state: {}", e);
-                    false
-                }
-            }
-        }
-        _ => {
-            match WasiUtils::create_wasi_env(&mut runtime.store, &module_name) {
-                Ok(env) => {
-                    runtime.wasi_env = Some(env);
-                    true
-                }
-                Err(e) => {
-                    rprintln!("Error creating WASI state: {}", e);
-                    false
-                }
-            }
-        }
+    if timeout.is_null() {
+        runtime.options.timeout = None;
+        return Ok(true);
+    }
+    if !runtime.options.interruptible {
+        return Err(WasmerError::argument("The runtime was created with 'interruptible = FALSE' and cannot time out calls"));
     }
+    runtime.options.timeout = Some(RuntimeOptions::seconds(&timeout, "timeout").map_err(WasmerError::argument)?);
+    Ok(true)
 }
 
-
-/// Compile a WAT (WebAssembly Text) module and add it to the runtime
+/// Interrupt the running call
 ///
-/// @title Compile WAT module
-/// @description Compile a WebAssembly Text (WAT) module and add it to the runtime.
-/// @family module compilation
-/// @seealso [wasmer_compile_wasm_ext()], [wasmer_wat_to_wasm_ext()]
+/// @title Interrupt the running call
+/// @description Stop the call currently running in an interruptible runtime as if the user had
+/// pressed Ctrl-C: it ends with a `wasmer_interrupt` condition once control returns to wasm code.
+/// Meant for host functions that decide the call should not go on. Calls into runtimes created
+/// with `interruptible = FALSE` are not affected.
+/// @family runtime management
+/// @seealso [wasmer_runtime_set_timeout_ext()], [wasmer_function_new_ext()]
 /// @param ptr External pointer to WasmerRuntime.
-/// @param wat_code WAT code as a string.
-/// @param module_name Name to register the module under.
-/// @return Status message
+/// @return TRUE if the runtime is interruptible, FALSE if the request has no effect
 /// @examples
 /// \dontrun{
-/// wasmer_compile_wat_ext(ptr, wat_code, "mod1")
+/// check <- wasmer_function_new_ext(ptr, function(i) {
+///     if (i > 1000) wasmer_runtime_interrupt_ext(ptr)
+///     i
+/// }, "i32", "i32", "check")
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_compile_wat_ext(mut ptr: ExternalPtr<WasmerRuntime>, wat_code: String, module_name: String) -> String {
-    let runtime = ptr.as_mut();
-    wasmer_compile_wat(runtime, wat_code, module_name)
+pub fn wasmer_runtime_interrupt_ext(ptr: ExternalPtr<WasmerRuntime>) -> bool {
+    if !ptr.as_ref().options.interruptible {
+        return false;
+    }
+    request_interrupt();
+    true
 }
 
-/// Compile a WASM binary and add it to the runtime
+/// List the WebAssembly proposals a compiler supports
 ///
-/// @title Compile WASM binary
-/// @description Compile a WebAssembly binary and add it to the runtime.
-/// @family module compilation
-/// @seealso [wasmer_compile_wat_ext()], [wasmer_wat_to_wasm_ext()]
-/// @param ptr External pointer to WasmerRuntime.
-/// @param wasm_bytes WASM binary as R raw vector.
-/// @param module_name Name to register the module under.
-/// @return Status message
+/// @title List compiler features
+/// @description List the WebAssembly proposals that can be toggled with the `features` option of
+/// [wasmer_runtime_new_with_compiler_ext()], whether the compiler supports each of them on this
+/// machine and whether it enables it by default.
+/// @family runtime management
+/// @seealso [wasmer_runtime_features_ext()]
+/// @param compiler_name Name of the compiler ("cranelift", "singlepass").
+/// @return Data frame with columns `feature`, `supported` and `default`
 /// @examples
 /// \dontrun{
-/// wasmer_compile_wasm_ext(ptr, wasm_bytes, "mod1")
+/// wasmer_compiler_features_ext("singlepass")
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_compile_wasm_ext(mut ptr: ExternalPtr<WasmerRuntime>, wasm_bytes: Robj, module_name: String) -> String {
-    let runtime = ptr.as_mut();
-    let bytes: Vec<u8> = match wasm_bytes.as_raw() {
-        Some(slice) => slice.as_slice().to_vec(),
-        None => return "Input is not a raw vector".to_string(),
+pub fn wasmer_compiler_features_ext(compiler_name: String) -> WasmerResult<Robj> {
+    let compiler_config = CompilerUtils::get_compiler_config(&compiler_name)
+        .map_err(WasmerError::argument)?;
+    let target = wasmer::sys::Target::default();
+    let (supported, defaults) = EngineFeatures::for_compiler(compiler_config.as_ref(), &target);
+    let column = |features: &wasmer_types::Features| -> Robj {
+        FEATURE_NAMES.iter().map(|name| EngineFeatures::get(features, name)).collect::<Vec<bool>>().into()
     };
-    match Module::new(&runtime.store, &bytes) {
-        Ok(module) => {
-            runtime.modules.insert(module_name.clone(), module);
-            format!("Module '{}' compiled from binary successfully", module_name)
-        }
-        Err(e) => format!("Error compiling module from binary: {}", e),
-    }
+    Ok(TypeConverter::data_frame(
+        &["feature", "supported", "default"],
+        vec![FEATURE_NAMES.to_vec().into(), column(&supported), column(&defaults)],
+    ))
 }
 
-/// Instantiate a compiled module in the runtime.
-/// @param ptr External pointer to WasmerRuntime
-/// @param module_name Name of the module to instantiate
-/// @param instance_name Name to register the instance under
-/// @return Status message
+/// List the WebAssembly proposals enabled in a runtime
+///
+/// @title List runtime features
+/// @description List which WebAssembly proposals the runtime's engine accepts: the compiler's
+/// defaults with the `features` option of [wasmer_runtime_new_with_compiler_ext()] applied.
+/// @family runtime management
+/// @seealso [wasmer_compiler_features_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @return Named logical vector
+/// @examples
+/// \dontrun{
+/// wasmer_runtime_features_ext(ptr)
+/// }
 /// @export
 #[extendr]
-pub fn wasmer_instantiate_ext(mut ptr: ExternalPtr<WasmerRuntime>, module_name: String, instance_name: String) -> String {
+pub fn wasmer_runtime_features_ext(mut ptr: ExternalPtr<WasmerRuntime>) -> Robj {
     let runtime = ptr.as_mut();
-    wasmer_instantiate(runtime, module_name, instance_name)
+    let mut enabled: Robj = FEATURE_NAMES
+        .iter()
+        .map(|name| EngineFeatures::get(&runtime.features, name))
+        .collect::<Vec<bool>>()
+        .into();
+    enabled.set_names(FEATURE_NAMES).unwrap();
+    enabled
 }
 
-/// Call an exported function from a WASM instance
+/// Describe the target of a runtime
 ///
-/// @title Call WASM function
-/// @description Call an exported function from a WASM instance.
+/// @title Describe the runtime target
+/// @description Describe the machine code a runtime generates: its target triple, the CPU
+/// features it may use and whether this machine can run it.
+/// @family runtime management
+/// @seealso [wasmer_runtime_new_with_compiler_ext()], [wasmer_module_serialize_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @return List with `triple`, `cpu_features` (character) and `runnable` (logical)
+/// @examples
+/// \dontrun{
+/// wasmer_runtime_target_ext(wasmer_runtime_new())$cpu_features
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_runtime_target_ext(mut ptr: ExternalPtr<WasmerRuntime>) -> List {
+    let runtime = ptr.as_mut();
+    let target = runtime.store.engine().target();
+    let mut cpu_features: Vec<String> = target.cpu_features().iter().map(|f| f.to_string()).collect();
+    cpu_features.sort();
+    List::from_names_and_values(
+        ["triple", "cpu_features", "runnable"],
+        [r!(target.triple().to_string()), r!(cpu_features), r!(EngineTarget::is_host_compatible(target))]
+    ).unwrap()
+}
+
+/// Create a WASI or WASIX state for the runtime
+///
+/// @title Create WASI/WASIX state
+/// @description Create a WASI or WASIX state for the runtime. Every instance created afterwards
+/// from a module importing WASI gets its own environment made from the state: its own arguments,
+/// stdin, output, filesystem and exit code, so several WASI programs can run side by side. The
+/// environment belongs to the instance and its handles; other modules are not affected.
+///
+/// `options` is a named list with any of:
+/// * `args`: character vector of program arguments, following the program name `module_name`.
+/// * `env`: named character vector of environment variables, e.g. `c(HOME = "/", LANG = "C")`.
+///   The host environment is not passed on.
+/// * `stdin`: what the program reads from stdin, as a raw vector or a character vector of lines.
+///   Empty by default; the R session's stdin is never used.
+/// * `stdin_file`: path of a host file to use as stdin instead, read when an instance is created.
+/// * `stdout`, `stderr`: `"capture"` (the default) keeps the program's output until it is read
+///   with [wasmer_wasi_output_ext()]; `"console"` also prints it to the R console as it is
+///   written, stderr as messages.
+/// * `stdout_file`, `stderr_file`: path of a host file receiving the output instead. The file is
+///   created, or truncated, when an instance is created.
+/// * `dirs`: named character vector of host directories the program can read and write, named
+///   by the path it sees them at, e.g. `c("/data" = "~/project/data")`. A leading `~` is
+///   expanded as by `path.expand()`.
+/// * `readonly_dirs`: likewise, for directories the program can only read.
+/// * `files`: named list of files, each a raw vector or a character vector of lines, named by
+///   their path. The program then sees an in-memory filesystem holding them, mounted at `/`,
+///   instead of host directories; read it back with [wasmer_wasi_files_ext()]. Each instance
+///   starts from these files.
+/// * `max_threads`: most threads a WASIX program may run at once; unlimited by default. Only
+///   for `env_type = "wasix"`.
+///
+/// Without `dirs`, `readonly_dirs` or `files` the program sees no filesystem.
+///
+/// A `"wasi"` state provides WASI preview 1 and runs a single thread; modules importing a WASIX
+/// ABI (see [wasmer_module_wasi_abi_ext()]) fail to instantiate with a `wasmer_link_error`. A
+/// `"wasix"` state also provides the `wasix_32v1` and `wasix_64v1` imports: the program can
+/// spawn threads, open sockets, which only reach the loopback interface, and fork and exec,
+/// which are emulated within the instance's environment.
+///
+/// Creating a new state only affects instances created afterwards.
+/// @family module instantiation
+/// @seealso [wasmer_module_wasi_abi_ext()], [wasmer_wasi_output_ext()], [wasmer_wasi_files_ext()], [wasmer_wasi_run_ext()], [wasmer_instantiate_ext()], [wasmer_instantiate_with_math_imports_ext()], [wasmer_instantiate_with_table_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param module_name Program name the programs see as their first argument.
+/// @param env_type Environment type: "wasi" (default) or "wasix".
+/// @param options Named list of WASI options, or NULL for the defaults.
+/// @return TRUE if successful
+/// @examples
+/// \dontrun{
+/// wasmer_wasi_state_new_ext(ptr, "mod1", "wasi")
+/// wasmer_wasi_state_new_ext(ptr, "mod1", "wasi", list(stdout = "console", stderr_file = "errors.log"))
+/// wasmer_wasi_state_new_ext(ptr, "wc", "wasi", list(args = c("-l"), env = c(LC_ALL = "C"), stdin_file = "reads.fa"))
+/// wasmer_wasi_state_new_ext(ptr, "align", "wasi", list(readonly_dirs = c("/ref" = "genomes"), dirs = c("/out" = "results")))
+/// wasmer_wasi_state_new_ext(ptr, "align", "wasi", list(files = list("/in/reads.fa" = c(">r1", "ACGT"))))
+/// wasmer_wasi_state_new_ext(ptr, "server", "wasix", list(max_threads = 4))
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_wasi_state_new_ext(
+    mut ptr: ExternalPtr<WasmerRuntime>,
+    module_name: String,
+    env_type: Option<String>,
+    #[default = "NULL"] options: Nullable<List>,
+) -> WasmerResult<bool> {
+    let runtime = ptr.as_mut();
+    let options = match options {
+        NotNull(options) => WasiOptions::from_list(&options).map_err(WasmerError::argument)?,
+        Null => WasiOptions::default(),
+    };
+    let env_type = env_type.unwrap_or_else(|| "wasi".to_string());
+    runtime.wasi = Some(wasi_config(&module_name, &env_type, options)?);
+    Ok(true)
+}
+
+/// Check WASI options and the environment type before environments are created from them
+fn wasi_config(program: &str, env_type: &str, options: WasiOptions) -> WasmerResult<WasiConfig> {
+    let env_type = WasiEnvType::parse(env_type).map_err(WasmerError::argument)?;
+    if env_type == WasiEnvType::Wasi && options.max_threads.is_some() {
+        return Err(WasmerError::argument("Option 'max_threads' requires env_type = \"wasix\""));
+    }
+    let config = WasiConfig { program: program.to_string(), env_type, options };
+    WasiUtils::check(&config)
+        .map_err(|e| WasmerError::runtime(format!("Error creating {} state: {}", env_type.name(), e)))?;
+    Ok(config)
+}
+
+fn create_wasi_state(store: &mut Store, config: &WasiConfig) -> WasmerResult<WasiState> {
+    let _guard = TOKIO_RUNTIME.enter();
+    WasiUtils::create_wasi_env(store, config)
+        .map_err(|e| WasmerError::runtime(format!("Error creating {} state: {}", config.env_type.name(), e)))
+}
+
+/// WASI environment of the instance called `instance_name`, or of the only WASI instance
+fn lookup_wasi<'a>(runtime: &'a WasmerRuntime, instance_name: Option<&str>) -> WasmerResult<&'a WasiState> {
+    if let Some(name) = instance_name {
+        lookup_instance(&runtime.instances, name)?;
+        return runtime.wasi_instances.get(name).ok_or_else(|| {
+            WasmerError::runtime(format!("Instance '{}' has no WASI state; its module does not import WASI or it was created before wasmer_wasi_state_new_ext()", name))
+        });
+    }
+    let mut wasi = runtime.wasi_instances.values();
+    match (wasi.next(), wasi.next()) {
+        (Some(wasi), None) => Ok(wasi),
+        (None, _) => Err(WasmerError::runtime("The runtime has no WASI state; create one with wasmer_wasi_state_new_ext() and instantiate a WASI module")),
+        (Some(_), Some(_)) => Err(WasmerError::argument("The runtime has several WASI instances; choose one with instance_name")),
+    }
+}
+
+/// Files of the in-memory filesystem of a WASI environment as a named list of raw vectors
+fn wasi_files(wasi: &WasiState) -> WasmerResult<List> {
+    let fs = wasi.memory_fs.as_ref().ok_or_else(|| {
+        WasmerError::runtime("The instance has no in-memory WASI filesystem; create one with the 'files' option of wasmer_wasi_state_new_ext()")
+    })?;
+    let _guard = TOKIO_RUNTIME.enter();
+    let files = WasiFs::read_all(fs).map_err(WasmerError::runtime)?;
+    let (paths, contents): (Vec<String>, Vec<Vec<u8>>) = files.into_iter().unzip();
+    Ok(List::from_names_and_values(paths, contents.into_iter().map(|bytes| r!(bytes))).unwrap())
+}
+
+/// Read the output of a WASI program
+///
+/// @title Read WASI output
+/// @description Return what a WASI instance wrote to stdout or stderr since the last read,
+/// and clear it. Output redirected to a file is not kept, so reading it returns nothing.
+/// @family module instantiation
+/// @seealso [wasmer_wasi_state_new_ext()], [wasmer_instance_wasi_output_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param stream "stdout" (default) or "stderr".
+/// @param format "character" (default) for a single string, invalid UTF-8 being replaced, or "raw" for the bytes.
+/// @param instance_name Name of the instance, or NULL when the runtime has a single WASI instance.
+/// @return String or raw vector
+/// @examples
+/// \dontrun{
+/// wasmer_call_function_ext(ptr, "inst1", "_start", list())
+/// cat(wasmer_wasi_output_ext(ptr))
+/// errors <- wasmer_wasi_output_ext(ptr, "stderr", "raw", instance_name = "inst1")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_wasi_output_ext(
+    mut ptr: ExternalPtr<WasmerRuntime>,
+    #[default = "\"stdout\""] stream: String,
+    #[default = "\"character\""] format: String,
+    #[default = "NULL"] instance_name: Nullable<String>,
+) -> WasmerResult<Robj> {
+    let runtime = ptr.as_mut();
+    let stream = OutputStream::parse(&stream).map_err(WasmerError::argument)?;
+    let format = OutputFormat::parse(&format).map_err(WasmerError::argument)?;
+    let instance_name = instance_name.into_option();
+    let wasi = lookup_wasi(runtime, instance_name.as_deref())?;
+    Ok(format.to_r(wasi.output(stream).take()))
+}
+
+/// Read the in-memory filesystem of a WASI program
+///
+/// @title Read WASI files
+/// @description Return every file of the in-memory filesystem of a WASI instance, created by
+/// the `files` option of [wasmer_wasi_state_new_ext()], with the contents the program left in it.
+/// @family module instantiation
+/// @seealso [wasmer_wasi_state_new_ext()], [wasmer_wasi_output_ext()], [wasmer_instance_wasi_files_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param instance_name Name of the instance, or NULL when the runtime has a single WASI instance.
+/// @return Named list of raw vectors, named by absolute path and sorted
+/// @examples
+/// \dontrun{
+/// wasmer_wasi_state_new_ext(ptr, "sort", "wasi", list(files = list("/in.txt" = c("b", "a"))))
+/// wasmer_call_function_ext(ptr, "inst1", "_start", list())
+/// rawToChar(wasmer_wasi_files_ext(ptr)[["/out.txt"]])
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_wasi_files_ext(mut ptr: ExternalPtr<WasmerRuntime>, #[default = "NULL"] instance_name: Nullable<String>) -> WasmerResult<List> {
+    let runtime = ptr.as_mut();
+    let instance_name = instance_name.into_option();
+    wasi_files(lookup_wasi(runtime, instance_name.as_deref())?)
+}
+
+/// Read the output of a WASI instance through its handle
+///
+/// @title Read WASI instance output
+/// @description Like [wasmer_wasi_output_ext()], for the WASI environment attached to an
+/// instance handle, which stays readable after the instance is removed from the runtime.
+/// @family module instantiation
+/// @seealso [wasmer_wasi_output_ext()], [wasmer_instance_wasi_files_ext()], [wasmer_module_instantiate_ext()]
+/// @param instance Instance handle from [wasmer_instance_handle_ext()] or [wasmer_module_instantiate_ext()].
+/// @param stream "stdout" (default) or "stderr".
+/// @param format "character" (default) or "raw".
+/// @return String or raw vector
+/// @examples
+/// \dontrun{
+/// inst <- wasmer_module_instantiate_ext(wasmer_module_handle_ext(ptr, "hello"), "hello1")
+/// wasmer_function_call_ext(wasmer_function_handle_ext(inst, "_start"), list())
+/// cat(wasmer_instance_wasi_output_ext(inst))
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_instance_wasi_output_ext(
+    instance: ExternalPtr<WasmerHandle>,
+    #[default = "\"stdout\""] stream: String,
+    #[default = "\"character\""] format: String,
+) -> WasmerResult<Robj> {
+    let stream = OutputStream::parse(&stream).map_err(WasmerError::argument)?;
+    let format = OutputFormat::parse(&format).map_err(WasmerError::argument)?;
+    let wasi = instance.as_ref().wasi()?;
+    Ok(format.to_r(wasi.output(stream).take()))
+}
+
+/// Read the in-memory filesystem of a WASI instance through its handle
+///
+/// @title Read WASI instance files
+/// @description Like [wasmer_wasi_files_ext()], for the WASI environment attached to an
+/// instance handle.
+/// @family module instantiation
+/// @seealso [wasmer_wasi_files_ext()], [wasmer_instance_wasi_output_ext()]
+/// @param instance Instance handle from [wasmer_instance_handle_ext()] or [wasmer_module_instantiate_ext()].
+/// @return Named list of raw vectors, named by absolute path and sorted
+/// @examples
+/// \dontrun{
+/// files <- wasmer_instance_wasi_files_ext(inst)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_instance_wasi_files_ext(instance: ExternalPtr<WasmerHandle>) -> WasmerResult<List> {
+    wasi_files(instance.as_ref().wasi()?)
+}
+
+/// Run a WASI command module
+///
+/// @title Run a WASI program
+/// @description Run the `_start` function of a WASI command module, like `wasmer run`, and
+/// return how it ended with everything it wrote. The module is instantiated for the run only,
+/// in its own WASI environment created from `options` as by [wasmer_wasi_state_new_ext()];
+/// the runtime's WASI state and instances are left alone.
+///
+/// A program calling `proc_exit()` ends with that exit code, `proc_exit(0)` being a success like
+/// returning from `_start`. A trap, timeout or interrupt raises the usual condition.
+/// @family module instantiation
+/// @seealso [wasmer_wasi_state_new_ext()], [wasmer_wasi_output_ext()], [wasmer_wasi_files_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param module_name Name of the module, also the program name the program sees.
+/// @param options Named list of WASI options, as for [wasmer_wasi_state_new_ext()], or NULL for the defaults.
+/// @param env_type Environment type: "wasi" (default) or "wasix".
+/// @param format "character" (default) or "raw", for the returned output as in [wasmer_wasi_output_ext()].
+/// @return List with `exit_code`, `success` (exit code 0), `stdout`, `stderr`, `elapsed`, the
+/// run time in seconds, and `files`, the in-memory filesystem as returned by
+/// [wasmer_wasi_files_ext()] or NULL without the `files` option. Output sent to the console is
+/// also returned; output redirected to a file is not.
+/// @examples
+/// \dontrun{
+/// wasmer_compile_wasm_ext(ptr, readBin("wc.wasm", "raw", file.size("wc.wasm")), "wc")
+/// result <- wasmer_wasi_run_ext(ptr, "wc", list(args = "-l", stdin = readLines("reads.fa")))
+/// if (result$success) cat(result$stdout) else stop(result$stderr)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_wasi_run_ext(
+    mut ptr: ExternalPtr<WasmerRuntime>,
+    module_name: String,
+    #[default = "NULL"] options: Nullable<List>,
+    #[default = "\"wasi\""] env_type: String,
+    #[default = "\"character\""] format: String,
+) -> WasmerResult<List> {
+    let runtime = ptr.as_mut();
+    let options = match options {
+        NotNull(options) => WasiOptions::from_list(&options).map_err(WasmerError::argument)?,
+        Null => WasiOptions::default(),
+    };
+    let format = OutputFormat::parse(&format).map_err(WasmerError::argument)?;
+    let config = wasi_config(&module_name, &env_type, options)?;
+    let run = wasi_run(runtime, &module_name, &config)?;
+    let files = match run.wasi.as_ref().filter(|wasi| wasi.memory_fs.is_some()) {
+        Some(wasi) => wasi_files(wasi)?.into(),
+        None => r!(NULL),
+    };
+    let output = |stream| run.wasi.as_ref().map_or_else(Vec::new, |wasi| wasi.output(stream).take());
+    Ok(List::from_names_and_values(
+        ["exit_code", "success", "stdout", "stderr", "elapsed", "files"],
+        [
+            r!(run.exit_code),
+            r!(run.exit_code == 0),
+            format.to_r(output(OutputStream::Stdout)),
+            format.to_r(output(OutputStream::Stderr)),
+            r!(run.elapsed),
+            files,
+        ],
+    )
+    .unwrap())
+}
+
+/// How a WASI program run by `wasmer_wasi_run_ext()` ended
+struct WasiRun {
+    exit_code: i32,
+    /// The program's environment, unless its module does not import WASI
+    wasi: Option<WasiState>,
+    /// Seconds spent in `_start`
+    elapsed: f64,
+}
+
+fn wasi_run(runtime: &mut WasmerRuntime, module_name: &str, config: &WasiConfig) -> WasmerResult<WasiRun> {
+    let module = lookup_module(&runtime.modules, module_name)?.clone();
+    let (instance, wasi) = link_instance(runtime, &module, module_name, module_name, Imports::new(), Some(config))?;
+    let start = instance
+        .exports
+        .get_function("_start")
+        .cloned()
+        .map_err(|_| WasmerError::export_not_found("function", "_start", module_name));
+
+    let _guard = TOKIO_RUNTIME.enter();
+    let started = Instant::now();
+    let result = start.and_then(|start| {
+        guarded_call(runtime, &instance, |store, _| match start.call(store, &[]) {
+            Ok(_) => Ok(0),
+            Err(e) => match e.downcast_ref::<wasmer_wasix::WasiError>() {
+                Some(wasmer_wasix::WasiError::Exit(code)) => Ok(code.raw()),
+                _ => Err(WasmerError::trap(module_name, "_start", e)),
+            },
+        })
+    });
+    let elapsed = started.elapsed().as_secs_f64();
+    // Release the environment's files and threads whether the program exited, trapped or
+    // could not be started
+    if let Some(wasi) = &wasi {
+        wasi.env.on_exit(&mut runtime.store, result.as_ref().ok().map(|&code| code.into()));
+    }
+    Ok(WasiRun { exit_code: result?, wasi, elapsed })
+}
+
+/// Compile a WAT (WebAssembly Text) module and add it to the runtime
+///
+/// @title Compile WAT module
+/// @description Compile a WebAssembly Text (WAT) module and add it to the runtime.
+/// Invalid WAT or a module that fails to compile raises a `wasmer_compile_error` condition.
+/// When the runtime has an artifact cache (see [wasmer_runtime_set_cache_dir_ext()]), a previously
+/// compiled module is loaded from the cache instead.
+/// @family module compilation
+/// @seealso [wasmer_compile_wasm_ext()], [wasmer_wat_to_wasm_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param wat_code WAT code as a string.
+/// @param module_name Name to register the module under.
+/// @return Status message
+/// @examples
+/// \dontrun{
+/// wasmer_compile_wat_ext(ptr, wat_code, "mod1")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_compile_wat_ext(mut ptr: ExternalPtr<WasmerRuntime>, wat_code: String, module_name: String) -> WasmerResult<String> {
+    let runtime = ptr.as_mut();
+    wasmer_compile_wat(runtime, wat_code, module_name)
+}
+
+/// Compile a WASM binary and add it to the runtime
+///
+/// @title Compile WASM binary
+/// @description Compile a WebAssembly binary and add it to the runtime.
+/// Invalid binaries raise a `wasmer_compile_error` condition.
+/// When the runtime has an artifact cache (see [wasmer_runtime_set_cache_dir_ext()]), a previously
+/// compiled binary is loaded from the cache instead.
+/// @family module compilation
+/// @seealso [wasmer_compile_wat_ext()], [wasmer_wat_to_wasm_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param wasm_bytes WASM binary as R raw vector.
+/// @param module_name Name to register the module under.
+/// @return Status message
+/// @examples
+/// \dontrun{
+/// wasmer_compile_wasm_ext(ptr, wasm_bytes, "mod1")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_compile_wasm_ext(mut ptr: ExternalPtr<WasmerRuntime>, wasm_bytes: Robj, module_name: String) -> WasmerResult<String> {
+    let runtime = ptr.as_mut();
+    let bytes: Vec<u8> = match wasm_bytes.as_raw() {
+        Some(slice) => slice.as_slice().to_vec(),
+        None => return Err(WasmerError::argument("wasm_bytes must be a raw vector")),
+    };
+    let (module, cached) = compile_module(runtime, &bytes, &module_name)?;
+    runtime.modules.insert(module_name.clone(), module);
+    if cached {
+        return Ok(format!("Module '{}' loaded from cache", module_name));
+    }
+    Ok(format!("Module '{}' compiled from binary successfully", module_name))
+}
+
+/// Validate a WASM binary or WAT module without compiling it
+///
+/// @title Validate WASM module
+/// @description Check that a module is valid under the proposals enabled in the runtime (see
+/// [wasmer_runtime_features_ext()]), without compiling it. This is much cheaper than
+/// [wasmer_compile_wasm_ext()] for large modules and works on headless runtimes.
+///
+/// Every invalid function body is reported; an error elsewhere in the module, such as a
+/// malformed section, ends the validation. WAT syntax errors are reported with an `NA` offset and
+/// the line and column in the message.
+/// @family module compilation
+/// @seealso [wasmer_compile_wasm_ext()], [wasmer_compile_wat_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param wasm WASM binary as a raw vector, or WAT code as a string.
+/// @return List with `valid` (logical) and `errors`, a data frame with the byte `offset` of each
+/// error in the binary, the index of the invalid `function` (`NA` outside function bodies) and the
+/// `message`
+/// @examples
+/// \dontrun{
+/// result <- wasmer_validate_ext(ptr, wasm_bytes)
+/// if (!result$valid) print(result$errors)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_validate_ext(mut ptr: ExternalPtr<WasmerRuntime>, wasm: Robj) -> WasmerResult<Robj> {
+    let runtime = ptr.as_mut();
+    let errors = if let Some(bytes) = wasm.as_raw() {
+        ModuleValidator::validate(bytes.as_slice(), &runtime.features)
+    } else if let Some(wat_code) = wasm.as_str() {
+        match wat2wasm(wat_code.as_bytes()) {
+            Ok(bytes) => ModuleValidator::validate(&bytes, &runtime.features),
+            Err(e) => vec![ValidationError { offset: None, function: None, message: format!("invalid WAT: {}", e) }],
+        }
+    } else {
+        return Err(WasmerError::argument("wasm must be a raw vector or a WAT string"));
+    };
+    Ok(ModuleValidator::report(&errors))
+}
+
+/// Serialize a compiled module to a raw vector or a file
+///
+/// @title Serialize WASM module
+/// @description Serialize a compiled module so that it can be loaded later with
+/// [wasmer_module_deserialize_ext()] without recompiling. The artifact records the compiler,
+/// target and wasmer version it was produced with and can only be loaded by a matching runtime.
+/// A runtime created with the `target` and `cpu_features` options produces artifacts for other
+/// machines, which load on any runtime with the same triple and at least those CPU features.
+///
+/// An artifact contains native machine code. Only share it where it cannot be tampered with, as
+/// whoever loads it runs that code.
+/// @family module compilation
+/// @seealso [wasmer_module_deserialize_ext()], [wasmer_runtime_set_cache_dir_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param module_name Name of the compiled module.
+/// @param path File to write the artifact to, or NULL to return it as a raw vector.
+/// @return Raw vector with the artifact, or `path` when it was written to a file
+/// @examples
+/// \dontrun{
+/// artifact <- wasmer_module_serialize_ext(ptr, "mod1")
+/// wasmer_module_serialize_ext(ptr, "mod1", "mod1.wasmu")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_module_serialize_ext(mut ptr: ExternalPtr<WasmerRuntime>, module_name: String, #[default = "NULL"] path: Nullable<String>) -> WasmerResult<Robj> {
+    let runtime = ptr.as_mut();
+    let module = match runtime.modules.get(&module_name) {
+        Some(module) => module,
+        None => return Err(WasmerError::not_found("module", &module_name)),
+    };
+    let bytes = ModuleCache::serialize(module, &runtime.engine_signature())
+        .map_err(WasmerError::runtime)?;
+    Ok(match path {
+        NotNull(path) => {
+            std::fs::write(&path, bytes)
+                .map_err(|e| WasmerError::runtime(format!("Cannot write '{}': {}", path, e)))?;
+            r!(path)
+        }
+        Null => r!(bytes),
+    })
+}
+
+/// Load a serialized module into the runtime
+///
+/// @title Deserialize WASM module
+/// @description Load a module serialized by [wasmer_module_serialize_ext()] and register it under
+/// `module_name`, skipping compilation. An artifact produced by another compiler (except on a
+/// headless runtime, see [wasmer_runtime_new_headless_ext()]), for another
+/// target or by another wasmer version, or a truncated or damaged one, raises a `wasmer_compile_error` condition.
+///
+/// Only load artifacts from a trusted source. Loading checks that an artifact is well formed, not
+/// that its native code is safe: a crafted artifact runs arbitrary code in the R process.
+/// @family module compilation
+/// @seealso [wasmer_module_serialize_ext()], [wasmer_instantiate_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param artifact Raw vector returned by [wasmer_module_serialize_ext()], or the path of an artifact file.
+/// @param module_name Name to register the module under.
+/// @return Status message
+/// @examples
+/// \dontrun{
+/// wasmer_module_deserialize_ext(ptr, artifact, "mod1")
+/// wasmer_module_deserialize_ext(ptr, "mod1.wasmu", "mod1")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_module_deserialize_ext(mut ptr: ExternalPtr<WasmerRuntime>, artifact: Robj, module_name: String) -> WasmerResult<String> {
+    let runtime = ptr.as_mut();
+    let bytes: Vec<u8> = if let Some(raw) = artifact.as_raw() {
+        raw.as_slice().to_vec()
+    } else if let Some(path) = artifact.as_str() {
+        std::fs::read(path)
+            .map_err(|e| WasmerError::runtime(format!("Cannot read '{}': {}", path, e)))?
+    } else {
+        return Err(WasmerError::argument("artifact must be a raw vector or a file path"));
+    };
+    let module = ModuleCache::deserialize(&runtime.store, &bytes, &runtime.engine_signature())
+        .map_err(|e| WasmerError::compile(&module_name, e))?;
+    runtime.modules.insert(module_name.clone(), module);
+    Ok(format!("Module '{}' deserialized successfully", module_name))
+}
+
+/// Enable or disable the compiled artifact cache of a runtime
+///
+/// @title Set artifact cache directory
+/// @description Enable a content-addressed cache of compiled modules in `cache_dir`, or disable it
+/// with NULL. With a cache, [wasmer_compile_wat_ext()] and [wasmer_compile_wasm_ext()] load the
+/// artifact of a previously compiled binary instead of recompiling it. Entries are keyed by the
+/// hash of the wasm binary, the target and the wasmer version, under one subdirectory per compiler.
+/// New runtimes use the directory given by `getOption("wasmer.cache_dir")`, if set.
+///
+/// Cached artifacts are native code loaded without further verification, so the directory must
+/// not be writable by anyone you do not trust. Directories the cache creates are private to the
+/// current user on Unix.
+/// @family runtime management
+/// @seealso [wasmer_module_serialize_ext()], [wasmer_compile_wasm_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param cache_dir Cache directory, created when needed, or NULL to disable the cache.
+/// @return TRUE if a cache is enabled
+/// @examples
+/// \dontrun{
+/// wasmer_runtime_set_cache_dir_ext(ptr, tools::R_user_dir("wasmer", "cache"))
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_runtime_set_cache_dir_ext(mut ptr: ExternalPtr<WasmerRuntime>, cache_dir: Nullable<String>) -> bool {
+    let runtime = ptr.as_mut();
+    runtime.cache = match cache_dir {
+        NotNull(dir) => Some(ModuleCache::new(dir)),
+        Null => None,
+    };
+    runtime.cache.is_some()
+}
+
+/// Instantiate a compiled module in the runtime.
+/// Missing or mismatched imports raise a `wasmer_link_error` condition, an unknown
+/// module a `wasmer_not_found` condition and a trapping start function a `wasmer_trap`.
+/// Reusing the name of an existing instance raises a `wasmer_argument_error`; remove the
+/// old instance with [wasmer_instance_remove_ext()] first.
+/// @param ptr External pointer to WasmerRuntime
+/// @param module_name Name of the module to instantiate
+/// @param instance_name Name to register the instance under
+/// @return Status message
+/// @export
+#[extendr]
+pub fn wasmer_instantiate_ext(mut ptr: ExternalPtr<WasmerRuntime>, module_name: String, instance_name: String) -> WasmerResult<String> {
+    let runtime = ptr.as_mut();
+    wasmer_instantiate(runtime, module_name, instance_name, Vec::new())
+}
+
+/// Call an exported function from a WASM instance
+///
+/// @title Call WASM function
+/// @description Call an exported function from a WASM instance. An unknown instance or
+/// function raises a `wasmer_not_found` condition and a trap raises a `wasmer_trap` condition;
+/// all conditions raised by the package inherit from `wasmer_error`.
+///
+/// Arguments are checked against the function signature before the call: the number of arguments
+/// must match, each must be a single non-`NA` value, and numbers must fit the parameter type
+/// (whole numbers for integer types; `i32` also accepts unsigned values up to 2^32 - 1).
+/// Invalid arguments raise a `wasmer_argument_error` condition. `i64` parameters
+/// accept integers, whole doubles, `bit64::integer64` values and decimal or `"0x"` hex strings.
+/// `i64` results are returned according to `getOption("wasmer.int64")`, read once per call:
+/// `"character"` (the default) returns decimal strings, `"integer64"` returns `bit64::integer64`
+/// and `"double"` returns doubles, possibly losing precision. These always return the same type;
+/// `"auto"` returns doubles when exactly representable and decimal strings otherwise.
+///
+/// A `wasmer_trap` condition carries `trap_code` (e.g. `"unreachable"`, `"out_of_bounds_memory"`,
+/// `"integer_overflow"`, `"stack_overflow"`, `"indirect_call_type_mismatch"`, or `NA` when the error
+/// was raised by a host function), `from_host`, and `frames`, a data frame of the wasm backtrace
+/// (innermost first) with columns `module`, `func_index`, `func_name`, `func_offset` and `module_offset`.
+/// When the runtime meters fuel, a call that runs out of it raises a trap of class `wasmer_out_of_fuel`
+/// with `trap_code` `"out_of_fuel"`. A call stopped by the runtime's timeout or by a user interrupt
+/// raises a trap of class `wasmer_timeout` (`trap_code` `"timeout"`) or `wasmer_interrupt`
+/// (`trap_code` `"interrupted"`).
+///
+/// An R error raised by a host function aborts the call and is re-raised as the original
+/// condition (message, class and call unchanged) with the fields `instance`, `function`,
+/// `frames` and `from_host = TRUE` added.
 /// @family function calling
 /// @seealso [wasmer_call_function_safe_ext()], [wasmer_host_function_example_ext()], [wasmer_math_example_ext()], [wasmer_hello_world_example_ext()]
 /// @param ptr External pointer to WasmerRuntime.
 /// @param instance_name Name of the instance.
 /// @param function_name Name of the function to call.
 /// @param args Arguments as R list.
-/// @return List with success flag and result or error
+/// @return The function result: a single value, or a list for multiple results
 /// @examples
 /// \dontrun{
 /// wasmer_call_function_ext(ptr, "inst1", "add", list(1, 2))
+/// tryCatch(wasmer_call_function_ext(ptr, "inst1", "kernel", list()),
+///          wasmer_trap = function(e) print(e$frames))
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_call_function_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, function_name: String, args: List) -> List {
+pub fn wasmer_call_function_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, function_name: String, args: List) -> WasmerResult<Robj> {
     let runtime = ptr.as_mut();
     wasmer_call_function(runtime, instance_name, function_name, args)
 }
 
+fn wasmer_call_function_vectorized(runtime: &mut WasmerRuntime, instance_name: String, function_name: String, args: List) -> WasmerResult<Robj> {
+    let (instance, func) = lookup_function(&runtime.instances, &instance_name, &function_name)?;
+    guarded_call(runtime, &instance, |store, int64_mode| {
+        VectorizedCall::call(store, &func, &args, &instance_name, &function_name, int64_mode)
+    })
+}
+
+/// Call an exported WASM function element-wise over R vectors
+///
+/// @title Call WASM function over vectors
+/// @description Calls an exported function once per element of the argument vectors, converting
+/// each argument vector to wasm values once and reusing the resolved function for every row,
+/// which avoids the per-call overhead of looping over [wasmer_call_function_ext()] in R.
+///
+/// `args` holds one vector per parameter, positionally; a data frame works directly, one column
+/// per parameter. All vectors must have the same length, except length-one vectors which are
+/// recycled. Elements are validated as in [wasmer_call_function_ext()] and an invalid element raises
+/// a `wasmer_argument_error` naming the argument and element. A trap aborts the whole call with
+/// a `wasmer_trap` condition whose message gives the failing row. With the `fuel_per_call` runtime
+/// option, all rows share one budget.
+/// @family function calling
+/// @seealso [wasmer_call_function_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param instance_name Name of the instance.
+/// @param function_name Name of the function to call.
+/// @param args List or data frame of argument vectors, one per parameter.
+/// @return A vector with one element per row for single-result functions, a data frame with
+/// columns `result1`, `result2`, ... for multi-value functions, or `NULL` when the function has no results
+/// @examples
+/// \dontrun{
+/// wasmer_call_function_vectorized_ext(ptr, "inst1", "add", list(1:10, 5L))
+/// wasmer_call_function_vectorized_ext(ptr, "inst1", "add", data.frame(a = 1:3, b = 4:6))
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_call_function_vectorized_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, function_name: String, args: List) -> WasmerResult<Robj> {
+    let runtime = ptr.as_mut();
+    wasmer_call_function_vectorized(runtime, instance_name, function_name, args)
+}
+
+/// Get the fuel left to an instance
+///
+/// @title Get remaining fuel
+/// @description Get the fuel left to an instance of a runtime created with the `fuel` option
+/// (see [wasmer_runtime_new_with_compiler_ext()]). Each executed wasm instruction costs one unit.
+/// @family function calling
+/// @seealso [wasmer_fuel_set_ext()], [wasmer_runtime_new_with_compiler_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param instance_name Name of the instance.
+/// @return Remaining fuel, 0 once exhausted, or NA when the instance is not metered
+/// @examples
+/// \dontrun{
+/// wasmer_fuel_remaining_ext(ptr, "inst1")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_fuel_remaining_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String) -> WasmerResult<Rfloat> {
+    let runtime = ptr.as_mut();
+    let instance = lookup_instance(&runtime.instances, &instance_name)?.clone();
+    Ok(match FuelMeter::remaining(&mut runtime.store, &instance) {
+        Some(points) => Rfloat::from(points as f64),
+        None => Rfloat::na(),
+    })
+}
+
+/// Set the fuel of an instance
+///
+/// @title Refill fuel
+/// @description Set the fuel of an instance of a runtime created with the `fuel` option, e.g. to
+/// let an instance that ran out of fuel run again.
+/// @family function calling
+/// @seealso [wasmer_fuel_remaining_ext()], [wasmer_runtime_new_with_compiler_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param instance_name Name of the instance.
+/// @param fuel New amount of fuel, a non-negative whole number.
+/// @return TRUE if successful
+/// @examples
+/// \dontrun{
+/// wasmer_fuel_set_ext(ptr, "inst1", 1e6)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_fuel_set_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, fuel: f64) -> WasmerResult<bool> {
+    let runtime = ptr.as_mut();
+    if !fuel.is_finite() || fuel < 0.0 || fuel.fract() != 0.0 {
+        return Err(WasmerError::argument(format!("fuel must be a non-negative whole number, got {}", fuel)));
+    }
+    // MAX_FUEL is not exactly representable and rounds up to 2^63, one past the limit
+    if fuel >= MAX_FUEL as f64 {
+        return Err(WasmerError::argument(format!("fuel must be at most {}, got {}", MAX_FUEL, fuel)));
+    }
+    let instance = lookup_instance(&runtime.instances, &instance_name)?.clone();
+    FuelMeter::refill(&mut runtime.store, &instance, fuel as u64)
+        .map_err(WasmerError::runtime)?;
+    Ok(true)
+}
+
 /// List all exports from a WASM instance
 ///
 /// @title List WASM exports
@@ -885,22 +1910,71 @@ pub fn wasmer_call_function_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_na
 /// @seealso [wasmer_list_function_signatures_ext()]
 /// @param ptr External pointer to WasmerRuntime.
 /// @param instance_name Name of the instance.
-/// @return List with success flag and exports or error
+/// @return Character vector of export names
 /// @examples
 /// \dontrun{
 /// wasmer_list_exports_ext(ptr, "inst1")
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_list_exports_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String) -> List {
+pub fn wasmer_list_exports_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String) -> WasmerResult<Vec<String>> {
     let runtime = ptr.as_mut();
     wasmer_list_exports(runtime, instance_name)
 }
 
+/// Register an instance's exports under an import namespace
+///
+/// @title Register instance for linking
+/// @description Register the exports of an existing instance under an import namespace. Later
+/// instantiations in the same runtime resolve `"namespace" "name"` imports from it automatically,
+/// like a linker. Explicitly supplied imports take precedence over registered ones.
+/// @family module instantiation
+/// @seealso [wasmer_unregister_namespace_ext()], [wasmer_instantiate_ext()], [wasmer_instantiate_with_imports_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param instance_name Name of the instance whose exports are registered.
+/// @param namespace Import namespace (module name) to register the exports under.
+/// @return TRUE if successful
+/// @examples
+/// \dontrun{
+/// wasmer_register_instance_ext(ptr, "alloc_inst", "alloc")
+/// wasmer_instantiate_ext(ptr, "kernel_mod", "kernel_inst")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_register_instance_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, namespace: String) -> WasmerResult<bool> {
+    let runtime = ptr.as_mut();
+    let instance = lookup_instance(&runtime.instances, &instance_name)?.clone();
+    runtime.linked_instances.insert(namespace, instance);
+    Ok(true)
+}
+
+/// Remove a namespace registered for linking
+///
+/// @title Unregister linked namespace
+/// @description Remove a namespace previously registered with [wasmer_register_instance_ext()].
+/// Instances already created keep their imports.
+/// @family module instantiation
+/// @seealso [wasmer_register_instance_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param namespace Import namespace to remove.
+/// @return TRUE if the namespace was registered
+/// @examples
+/// \dontrun{
+/// wasmer_unregister_namespace_ext(ptr, "alloc")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_unregister_namespace_ext(mut ptr: ExternalPtr<WasmerRuntime>, namespace: String) -> bool {
+    let runtime = ptr.as_mut();
+    runtime.linked_instances.remove(&namespace).is_some()
+}
+
 /// Register an R function for use as a host function in WASM (per-runtime)
 ///
 /// @title Register R host function
 /// @description Register an R function for use as a host function in WASM (per-runtime).
+/// An error raised by the R function aborts the wasm call and is re-raised at the caller as the
+/// original condition.
 /// @family host function registration
 /// @seealso [wasmer_function_new_ext()], [wasmer_function_new_i32_to_i32()], [wasmer_function_new_i32_i32_to_i32()], [wasmer_function_new_f64_f64_to_f64()], [wasmer_function_new_f64_to_f64()], [wasmer_function_new_i32_to_void()], [wasmer_function_new_void_to_i32()]
 /// @param ptr External pointer to WasmerRuntime.
@@ -927,17 +2001,17 @@ pub fn wasmer_register_r_function_ext(mut ptr: ExternalPtr<WasmerRuntime>, name:
 /// @family module compilation
 /// @seealso [wasmer_compile_wat_ext()], [wasmer_compile_wasm_ext()]
 /// @param wat_code WAT code as a string.
-/// @return WASM binary as R raw vector, or error string if conversion fails
+/// @return WASM binary as R raw vector
 /// @examples
 /// \dontrun{
 /// wasmer_wat_to_wasm_ext(wat_code)
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_wat_to_wasm_ext(wat_code: String) -> Robj {
+pub fn wasmer_wat_to_wasm_ext(wat_code: String) -> WasmerResult<Robj> {
     match wasmer::wat2wasm(wat_code.as_bytes()) {
-        Ok(wasm_bytes) => r!(wasm_bytes.into_owned()),
-        Err(e) => r!(format!("Error converting WAT to WASM: {}", e)),
+        Ok(wasm_bytes) => Ok(r!(wasm_bytes.into_owned())),
+        Err(e) => Err(WasmerError::Compile { module: None, message: format!("invalid WAT: {}", e) }),
     }
 }
 
@@ -957,21 +2031,14 @@ pub fn wasmer_wat_to_wasm_ext(wat_code: String) -> Robj {
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_memory_size_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, memory_name: String) -> List {
+pub fn wasmer_memory_size_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, memory_name: String) -> WasmerResult<List> {
     let runtime = ptr.as_mut();
-    if let Some(instance) = runtime.instances.get(&instance_name) {
-        if let Ok(memory) = instance.exports.get_memory(&memory_name) {
-            let view = memory.view(&runtime.store);
-            List::from_names_and_values(
-                ["size_bytes", "size_pages"],
-                [r!(view.data_size()), r!(view.size().0)]
-            ).unwrap()
-        } else {
-            List::from_names_and_values(["error"], [r!("Memory not found")]).unwrap()
-        }
-    } else {
-        List::from_names_and_values(["error"], [r!("Instance not found")]).unwrap()
-    }
+    let memory = lookup_memory(&runtime.instances, &instance_name, &memory_name)?;
+    let view = memory.view(&runtime.store);
+    Ok(List::from_names_and_values(
+        ["size_bytes", "size_pages"],
+        [r!(view.data_size()), r!(view.size().0)]
+    ).unwrap())
 }
 
 /// Read bytes from WASM memory
@@ -992,29 +2059,27 @@ pub fn wasmer_memory_size_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_memory_read_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, memory_name: String, offset: i32, length: i32) -> Robj {
+pub fn wasmer_memory_read_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, memory_name: String, offset: i32, length: i32) -> WasmerResult<Robj> {
     let runtime = ptr.as_mut();
-    if let Some(instance) = runtime.instances.get(&instance_name) {
-        if let Ok(memory) = instance.exports.get_memory(&memory_name) {
-            let view = memory.view(&runtime.store);
-            let mut bytes = Vec::new();
-            let data_size = view.data_size().try_into().unwrap();
-            for i in 0..length {
-                let idx = offset + i;
-                if idx >= 0 && (idx as usize) < data_size {
-                    // Read byte using data_ptr
-                    unsafe {
-                        bytes.push(*view.data_ptr().add(idx as usize));
-                    }
-                }
-            }
-            r!(bytes)
-        } else {
-            r!("Memory not found")
-        }
-    } else {
-        r!("Instance not found")
+    let memory = lookup_memory(&runtime.instances, &instance_name, &memory_name)?;
+    Ok(r!(read_memory_bytes(&runtime.store, &memory, offset, length)?))
+}
+
+/// Read up to `length` bytes starting at `offset`, stopping at the end of the memory
+fn read_memory_bytes(store: &Store, memory: &Memory, offset: i32, length: i32) -> WasmerResult<Vec<u8>> {
+    if offset < 0 || length < 0 {
+        return Err(WasmerError::argument(format!(
+            "offset and length must be non-negative, got {} and {}",
+            offset, length
+        )));
     }
+    let view = memory.view(store);
+    let start = (offset as u64).min(view.data_size());
+    let end = (offset as u64 + length as u64).min(view.data_size());
+    let mut bytes = vec![0; (end - start) as usize];
+    view.read(start, &mut bytes)
+        .map_err(|e| WasmerError::runtime(format!("Failed to read memory: {}", e)))?;
+    Ok(bytes)
 }
 
 /// Write bytes to WASM memory
@@ -1026,35 +2091,40 @@ pub fn wasmer_memory_read_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name
 /// @param ptr External pointer to WasmerRuntime.
 /// @param instance_name Name of the instance.
 /// @param memory_name Name of the exported memory.
-/// @param offset Offset to start writing.
+/// @param offset Non-negative offset to start writing.
 /// @param bytes Raw vector of bytes to write.
-/// @return TRUE if successful
+/// @return TRUE if successful, FALSE if the bytes do not fit in the memory
 /// @examples
 /// \dontrun{
 /// wasmer_memory_write_ext(ptr, "inst1", "memory", 0, as.raw(c(1,2,3)))
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_memory_write_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, memory_name: String, offset: i32, bytes: Robj) -> bool {
+pub fn wasmer_memory_write_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, memory_name: String, offset: i32, bytes: Robj) -> WasmerResult<bool> {
     let runtime = ptr.as_mut();
-    if let Some(instance) = runtime.instances.get(&instance_name) {
-        if let Ok(memory) = instance.exports.get_memory(&memory_name) {
-            let view = memory.view(&runtime.store);
-            if let Some(slice) = bytes.as_raw() {
-                let start = offset as usize;
-                let end = start + slice.len();
-                let data_size = view.data_size().try_into().unwrap();
-                if end <= data_size {
-                    unsafe {
-                        let data_mut = view.data_ptr();
-                        std::ptr::copy_nonoverlapping(slice.as_slice().as_ptr(), data_mut.add(start), slice.len());
-                    }
-                    return true;
-                }
-            }
+    let memory = lookup_memory(&runtime.instances, &instance_name, &memory_name)?;
+    write_memory_bytes(&runtime.store, &memory, offset, &bytes)
+}
+
+/// Write a raw vector at `offset`; returns FALSE when it does not fit in the memory
+fn write_memory_bytes(store: &Store, memory: &Memory, offset: i32, bytes: &Robj) -> WasmerResult<bool> {
+    let slice = match bytes.as_raw() {
+        Some(slice) => slice,
+        None => return Err(WasmerError::argument("bytes must be a raw vector")),
+    };
+    if offset < 0 {
+        return Err(WasmerError::argument(format!("offset must be non-negative, got {}", offset)));
+    }
+    let view = memory.view(store);
+    let start = offset as u64;
+    match start.checked_add(slice.len() as u64) {
+        Some(end) if end <= view.data_size() => {
+            view.write(start, slice.as_slice())
+                .map_err(|e| WasmerError::runtime(format!("Failed to write memory: {}", e)))?;
+            Ok(true)
         }
+        _ => Ok(false),
     }
-    false
 }
 
 /// Read UTF-8 string from WASM memory
@@ -1062,71 +2132,115 @@ pub fn wasmer_memory_write_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_nam
 /// @title Read WASM memory as string
 /// @description Read UTF-8 string from WASM memory.
 /// @family memory operations
-/// @seealso [wasmer_memory_size_ext()], [wasmer_memory_read_ext()], [wasmer_memory_write_ext()], [wasmer_memory_grow_ext()]
+/// @seealso [wasmer_memory_size_ext()], [wasmer_memory_read_ext()], [wasmer_memory_write_ext()], [wasmer_memory_grow_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param instance_name Name of the instance.
+/// @param memory_name Name of the exported memory.
+/// @param offset Offset to start reading.
+/// @param length Number of bytes to read.
+/// @return String
+/// @examples
+/// \dontrun{
+/// wasmer_memory_read_string_ext(ptr, "inst1", "memory", 0, 10)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_memory_read_string_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, memory_name: String, offset: i32, length: i32) -> WasmerResult<String> {
+    let runtime = ptr.as_mut();
+    let memory = lookup_memory(&runtime.instances, &instance_name, &memory_name)?;
+    let bytes = read_memory_bytes(&runtime.store, &memory, offset, length)?;
+    Ok(String::from_utf8(bytes).unwrap_or_else(|_| "".to_string()))
+}
+
+/// Grow WASM memory by a number of pages
+///
+/// @title Grow WASM memory
+/// @description Grow WASM memory by a number of pages. Growing past the maximum of the memory,
+/// which is lowered to the runtime's `max_memory_pages` option when set, raises a
+/// `wasmer_limit_error` condition with fields `resource`, `requested` and `limit`. Within wasm,
+/// `memory.grow` returns -1 instead.
+/// @family memory operations
+/// @seealso [wasmer_memory_size_ext()], [wasmer_memory_read_ext()], [wasmer_memory_write_ext()], [wasmer_memory_read_string_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param instance_name Name of the instance.
+/// @param memory_name Name of the exported memory.
+/// @param pages Number of pages to grow.
+/// @return TRUE if successful
+/// @examples
+/// \dontrun{
+/// wasmer_memory_grow_ext(ptr, "inst1", "memory", 1)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_memory_grow_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, memory_name: String, pages: u32) -> WasmerResult<bool> {
+    let runtime = ptr.as_mut();
+    let memory = lookup_memory(&runtime.instances, &instance_name, &memory_name)?;
+    grow_memory(&mut runtime.store, &memory, pages)?;
+    Ok(true)
+}
+
+/// Grow a memory, reporting growth past its maximum as a limit error
+fn grow_memory(store: &mut Store, memory: &Memory, pages: u32) -> WasmerResult<()> {
+    let maximum = memory.ty(store).maximum.unwrap_or(Pages::max_value());
+    let requested = memory.view(store).size().0 as u64 + pages as u64;
+    if requested > maximum.0 as u64 {
+        return Err(WasmerError::limit("memory", requested, maximum.0 as u64));
+    }
+    memory
+        .grow(store, pages)
+        .map(|_| ())
+        .map_err(|e| WasmerError::runtime(format!("Failed to grow memory: {}", e)))
+}
+
+/// Create a new WASM Memory
+///
+/// @title Create WASM Memory
+/// @description Create a new WASM linear memory that can be passed as an import. The maximum is
+/// lowered to the runtime's `max_memory_pages` option when set, and a larger `min_pages` raises a
+/// `wasmer_limit_error` condition.
+/// @family memory operations
+/// @seealso [wasmer_get_exported_memory_ext()], [wasmer_instantiate_with_imports_ext()]
 /// @param ptr External pointer to WasmerRuntime.
-/// @param instance_name Name of the instance.
-/// @param memory_name Name of the exported memory.
-/// @param offset Offset to start reading.
-/// @param length Number of bytes to read.
-/// @return String
+/// @param min_pages Initial size in 64 KiB pages.
+/// @param max_pages Maximum size in pages (optional).
+/// @param shared Whether the memory is shared (requires the threads proposal).
+/// @return External pointer to Memory
 /// @examples
 /// \dontrun{
-/// wasmer_memory_read_string_ext(ptr, "inst1", "memory", 0, 10)
+/// wasmer_memory_new_ext(ptr, 1, 16, FALSE)
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_memory_read_string_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, memory_name: String, offset: i32, length: i32) -> String {
+pub fn wasmer_memory_new_ext(mut ptr: ExternalPtr<WasmerRuntime>, min_pages: u32, max_pages: Option<u32>, shared: bool) -> WasmerResult<ExternalPtr<Memory>> {
     let runtime = ptr.as_mut();
-    if let Some(instance) = runtime.instances.get(&instance_name) {
-        if let Ok(memory) = instance.exports.get_memory(&memory_name) {
-            let view = memory.view(&runtime.store);
-            let data_size = view.data_size().try_into().unwrap();
-            let mut bytes = Vec::new();
-            for i in 0..length {
-                let idx = offset + i;
-                if idx >= 0 && (idx as usize) < data_size {
-                    unsafe {
-                        bytes.push(*view.data_ptr().add(idx as usize));
-                    }
-                }
-            }
-            String::from_utf8(bytes).unwrap_or_else(|_| "".to_string())
-        } else {
-            "Memory not found".to_string()
-        }
-    } else {
-        "Instance not found".to_string()
+    if let Some(limit) = runtime.options.max_memory_pages.filter(|limit| min_pages > *limit) {
+        return Err(WasmerError::limit("memory", min_pages as u64, limit as u64));
     }
+    let memory_type = MemoryType::new(Pages(min_pages), max_pages.map(Pages), shared);
+    let memory = Memory::new(&mut runtime.store, memory_type)
+        .map_err(|e| WasmerError::runtime(format!("Failed to create memory: {}", e)))?;
+    Ok(ExternalPtr::new(memory))
 }
 
-/// Grow WASM memory by a number of pages
+/// Get a pointer to an exported memory from a WASM instance by name
 ///
-/// @title Grow WASM memory
-/// @description Grow WASM memory by a number of pages.
+/// @title Get exported WASM Memory
+/// @description Get a pointer to an exported memory from a WASM instance by name.
 /// @family memory operations
-/// @seealso [wasmer_memory_size_ext()], [wasmer_memory_read_ext()], [wasmer_memory_write_ext()], [wasmer_memory_read_string_ext()]
+/// @seealso [wasmer_memory_new_ext()], [wasmer_instantiate_with_imports_ext()]
 /// @param ptr External pointer to WasmerRuntime.
 /// @param instance_name Name of the instance.
 /// @param memory_name Name of the exported memory.
-/// @param pages Number of pages to grow.
-/// @return TRUE if successful
+/// @return External pointer to Memory
 /// @examples
 /// \dontrun{
-/// wasmer_memory_grow_ext(ptr, "inst1", "memory", 1)
+/// wasmer_get_exported_memory_ext(ptr, "inst1", "memory")
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_memory_grow_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, memory_name: String, pages: u32) -> bool {
+pub fn wasmer_get_exported_memory_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, memory_name: String) -> WasmerResult<ExternalPtr<Memory>> {
     let runtime = ptr.as_mut();
-    if let Some(instance) = runtime.instances.get(&instance_name) {
-        if let Ok(memory) = instance.exports.get_memory(&memory_name) {
-            memory.grow(&mut runtime.store, pages).is_ok()
-        } else {
-            false
-        }
-    } else {
-        false
-    }
+    Ok(ExternalPtr::new(lookup_memory(&runtime.instances, &instance_name, &memory_name)?))
 }
 
 /// Instantiate a compiled module in the runtime, with a custom table import
@@ -1151,31 +2265,84 @@ pub fn wasmer_instantiate_with_table_ext(
     module_name: String,
     instance_name: String,
     mut table_ptr: ExternalPtr<Table>
-) -> String {
+) -> WasmerResult<String> {
     let runtime = ptr.as_mut();
-    if let Some(module) = runtime.modules.get(&module_name) {
-        let import_object = imports! {
-            "env" => {
-                "host_table" => table_ptr.as_mut().clone(),
-            }
-        };
-        match Instance::new(&mut runtime.store, module, &import_object) {
-            Ok(final_instance) => {
-                runtime.instances.insert(instance_name.clone(), final_instance.clone());
-                format!("Instance '{}' created successfully with table import", instance_name)
-            }
-            Err(e) => format!("Error creating instance: {}", e),
+    let import_object = imports! {
+        "env" => {
+            "host_table" => table_ptr.as_mut().clone(),
+        }
+    };
+    instantiate_module(runtime, &module_name, &instance_name, import_object)?;
+    Ok(format!("Instance '{}' created successfully with table import", instance_name))
+}
+
+/// Instantiate a compiled module in the runtime, with global imports
+///
+/// @title Instantiate WASM module with global imports
+/// @description Instantiate a compiled WASM module in the runtime, importing R-created globals into the "env" namespace next to "r_host_call".
+/// @family module instantiation
+/// @seealso [wasmer_instantiate_ext()], [wasmer_global_new_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param module_name Name of the module to instantiate.
+/// @param instance_name Name to register the instance under.
+/// @param globals Named list of Global pointers, imported as "env.<name>".
+/// @return Status message
+/// @examples
+/// \dontrun{
+/// sp <- wasmer_global_new_ext(ptr, 65536L, "i32", TRUE)
+/// wasmer_instantiate_with_globals_ext(ptr, "mod1", "inst1", list(stack_pointer = sp))
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_instantiate_with_globals_ext(mut ptr: ExternalPtr<WasmerRuntime>, module_name: String, instance_name: String, globals: List) -> WasmerResult<String> {
+    let runtime = ptr.as_mut();
+    let mut global_imports = Vec::new();
+    for (name, value) in globals.iter() {
+        match ExternalPtr::<Global>::try_from(value) {
+            Ok(global) => global_imports.push((name.to_string(), global.as_ref().clone())),
+            Err(_) => return Err(WasmerError::argument(format!("Import '{}' is not a Global pointer", name))),
         }
-    } else {
-        format!("Module '{}' not found", module_name)
     }
+    wasmer_instantiate(runtime, module_name, instance_name, global_imports)
+}
+
+/// Instantiate a compiled module in the runtime with user-defined imports
+///
+/// @title Instantiate WASM module with custom imports
+/// @description Instantiate a compiled WASM module using an import object built from a nested R list of
+/// namespaces, e.g. `list(env = list(foo = <Function>, mem = <Memory>, tbl = <Table>, g = <Global>))`.
+/// Entries can be any external pointer created by [wasmer_function_new_ext()], [wasmer_memory_new_ext()],
+/// [wasmer_table_new_ext()], [wasmer_global_new_ext()] or their exported counterparts, or function and
+/// memory handles from [wasmer_function_handle_ext()] and [wasmer_memory_handle_ext()].
+/// @family module instantiation
+/// @seealso [wasmer_instantiate_ext()], [wasmer_instantiate_with_globals_ext()], [wasmer_instantiate_with_table_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param module_name Name of the module to instantiate.
+/// @param instance_name Name to register the instance under.
+/// @param imports Named list of namespaces, each a named list of external pointers.
+/// @return Status message
+/// @examples
+/// \dontrun{
+/// f <- wasmer_function_new_ext(ptr, function(x) x * 2, c("i32"), c("i32"), "double")
+/// mem <- wasmer_memory_new_ext(ptr, 1, NULL, FALSE)
+/// wasmer_instantiate_with_imports_ext(ptr, "mod1", "inst1", list(env = list(double = f, memory = mem)))
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_instantiate_with_imports_ext(mut ptr: ExternalPtr<WasmerRuntime>, module_name: String, instance_name: String, imports: List) -> WasmerResult<String> {
+    let runtime = ptr.as_mut();
+    let import_object = ImportUtils::imports_from_list(&imports)
+        .map_err(|e| WasmerError::argument(format!("Error building imports: {}", e)))?;
+    instantiate_module(runtime, &module_name, &instance_name, import_object)?;
+    Ok(format!("Instance '{}' created successfully", instance_name))
 }
 
 
 /// Create a new WASM Table
 ///
 /// @title Create WASM Table
-/// @description Create a new WASM Table.
+/// @description Create a new WASM Table. The maximum is lowered to the runtime's
+/// `max_table_elements` option when set, and a larger `min` raises a `wasmer_limit_error` condition.
 /// @family table operations
 /// @seealso [wasmer_table_set_ext()], [wasmer_table_grow_ext()], [wasmer_table_get_ext()], [wasmer_get_exported_table_ext()]
 /// @param ptr External pointer to WasmerRuntime.
@@ -1188,11 +2355,15 @@ pub fn wasmer_instantiate_with_table_ext(
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_table_new_ext(mut ptr: ExternalPtr<WasmerRuntime>, min: u32, max: Option<u32>) -> ExternalPtr<Table> {
+pub fn wasmer_table_new_ext(mut ptr: ExternalPtr<WasmerRuntime>, min: u32, max: Option<u32>) -> WasmerResult<ExternalPtr<Table>> {
     let runtime = ptr.as_mut();
+    if let Some(limit) = runtime.options.max_table_elements.filter(|limit| min > *limit) {
+        return Err(WasmerError::limit("table", min as u64, limit as u64));
+    }
     let table_type = TableType::new(Type::FuncRef, min, max);
-    let table = Table::new(&mut runtime.store, table_type, Value::FuncRef(None)).unwrap();
-    ExternalPtr::new(table)
+    let table = Table::new(&mut runtime.store, table_type, Value::FuncRef(None))
+        .map_err(|e| WasmerError::runtime(format!("Failed to create table: {}", e)))?;
+    Ok(ExternalPtr::new(table))
 }
 
 /// Set a function reference in a WASM Table
@@ -1222,7 +2393,9 @@ pub fn wasmer_table_set_ext(mut ptr: ExternalPtr<WasmerRuntime>, mut table_ptr:
 /// Grow a WASM Table
 ///
 /// @title Grow WASM Table
-/// @description Grow a WASM Table by a number of elements.
+/// @description Grow a WASM Table by a number of elements. Growing past the maximum of the table,
+/// which is lowered to the runtime's `max_table_elements` option when set, raises a
+/// `wasmer_limit_error` condition.
 /// @family table operations
 /// @seealso [wasmer_table_new_ext()], [wasmer_table_set_ext()], [wasmer_table_get_ext()], [wasmer_get_exported_table_ext()]
 /// @param ptr External pointer to WasmerRuntime.
@@ -1236,11 +2409,18 @@ pub fn wasmer_table_set_ext(mut ptr: ExternalPtr<WasmerRuntime>, mut table_ptr:
 /// }
 /// @export
 #[extendr]
-pub fn wasmer_table_grow_ext(mut ptr: ExternalPtr<WasmerRuntime>, mut table_ptr: ExternalPtr<Table>, delta: u32, mut func_ptr: ExternalPtr<Function>) -> u32 {
+pub fn wasmer_table_grow_ext(mut ptr: ExternalPtr<WasmerRuntime>, mut table_ptr: ExternalPtr<Table>, delta: u32, mut func_ptr: ExternalPtr<Function>) -> WasmerResult<u32> {
     let runtime = ptr.as_mut();
     let table = table_ptr.as_mut();
     let func = func_ptr.as_mut();
-    table.grow(&mut runtime.store, delta, func.clone().into()).unwrap_or(0)
+    let requested = table.size(&runtime.store) as u64 + delta as u64;
+    let maximum = table.ty(&runtime.store).maximum.unwrap_or(u32::MAX);
+    if requested > maximum as u64 {
+        return Err(WasmerError::limit("table", requested, maximum as u64));
+    }
+    table
+        .grow(&mut runtime.store, delta, func.clone().into())
+        .map_err(|e| WasmerError::runtime(format!("Failed to grow table: {}", e)))
 }
 
 /// Get a function reference from a WASM Table
@@ -1273,6 +2453,8 @@ pub fn wasmer_table_get_ext(mut ptr: ExternalPtr<WasmerRuntime>, mut table_ptr:
 ///
 /// @title Create dynamic R host function
 /// @description Create a Wasmer host function from an R function with dynamic signature.
+/// The R function receives one argument per wasm parameter. An error raised by the R function
+/// aborts the wasm call and is re-raised at the caller as the original condition.
 /// @family host function registration
 /// @seealso [wasmer_register_r_function_ext()], [wasmer_function_new_i32_to_i32()], [wasmer_function_new_i32_i32_to_i32()], [wasmer_function_new_f64_f64_to_f64()], [wasmer_function_new_f64_to_f64()], [wasmer_function_new_i32_to_void()], [wasmer_function_new_void_to_i32()]
 /// @param ptr External pointer to WasmerRuntime.
@@ -1293,82 +2475,48 @@ pub fn wasmer_function_new_ext(
     arg_types: Vec<String>,
     ret_types: Vec<String>,
     _name: String
-) -> ExternalPtr<Function> {
+) -> WasmerResult<ExternalPtr<Function>> {
     let runtime = ptr.as_mut();
+    let parse_types = |types: &[String]| -> WasmerResult<Vec<Type>> {
+        types
+            .iter()
+            .map(|s| TypeConverter::parse_type(s).map_err(WasmerError::argument))
+            .collect()
+    };
+    let param_types = parse_types(&arg_types)?;
+    let result_types = parse_types(&ret_types)?;
     let id = register_r_function_internal(rfun);
-    fn str_to_type(s: &str) -> Type {
-        match s.to_lowercase().as_str() {
-            "i32" => Type::I32,
-            "i64" => Type::I64,
-            "f32" => Type::F32,
-            "f64" => Type::F64,
-            _ => Type::I32,
-        }
-    }
-    let param_types: Vec<Type> = arg_types.iter().map(|s| str_to_type(s)).collect();
-    let result_types: Vec<Type> = ret_types.iter().map(|s| str_to_type(s)).collect();
     let fn_type = wasmer::FunctionType::new(param_types.clone(), result_types.clone());
     let fun = Function::new(
         &mut runtime.store,
         &fn_type,
         move |args: &[Value]| -> std::result::Result<Vec<Value>, wasmer::RuntimeError> {
-            let r_args: Vec<Robj> = args.iter().map(|v| match v {
-                Value::I32(i) => r!(*i),
-                Value::I64(i) => r!(*i as i64),
-                Value::F32(f) => r!(*f as f32),
-                Value::F64(f) => r!(*f as f64),
-                _ => r!(0),
-            }).collect();
-            let result = R_FUNCTION_REGISTRY.with(|reg| {
-                reg.borrow().get(&id).cloned()
-            }).and_then(|rfun| {
-                rfun.call(pairlist!(r_args)).ok()
-            });
-            if let Some(r) = result {
-                if result_types.len() == 0 {
-                    Ok(vec![])
-                } else if result_types.len() == 1 {
-                    let v = match result_types[0] {
-                        Type::I32 => Value::I32(r.as_integer().unwrap_or(0)),
-                        Type::I64 => Value::I64(r.as_real().unwrap_or(0.0) as i64),
-                        Type::F32 => Value::F32(r.as_real().unwrap_or(0.0) as f32),
-                        Type::F64 => Value::F64(r.as_real().unwrap_or(0.0)),
-                        _ => Value::I32(0),
-                    };
-                    Ok(vec![v])
-                } else {
-                    // Multiple return values: expect R to return a list/vector
-                    let mut out = Vec::new();
-                    if let Some(list) = r.as_list() {
-                        for (i, ty) in result_types.iter().enumerate() {
-                            let rv = list.iter().nth(i).map(|(_, v)| v).unwrap_or(r!(0));
-                            let v = match ty {
-                                Type::I32 => Value::I32(rv.as_integer().unwrap_or(0)),
-                                Type::I64 => Value::I64(rv.as_real().unwrap_or(0.0) as i64),
-                                Type::F32 => Value::F32(rv.as_real().unwrap_or(0.0) as f32),
-                                Type::F64 => Value::F64(rv.as_real().unwrap_or(0.0)),
-                                _ => Value::I32(0),
-                            };
-                            out.push(v);
-                        }
-                        Ok(out)
-                    } else {
-                        let v = match result_types[0] {
-                            Type::I32 => Value::I32(r.as_integer().unwrap_or(0)),
-                            Type::I64 => Value::I64(r.as_real().unwrap_or(0.0) as i64),
-                            Type::F32 => Value::F32(r.as_real().unwrap_or(0.0) as f32),
-                            Type::F64 => Value::F64(r.as_real().unwrap_or(0.0)),
-                            _ => Value::I32(0),
-                        };
-                        Ok(vec![v])
+            // Within a call this is the mode guarded_call read, so the option is not read per callback
+            let int64_mode = Int64Mode::current();
+            let r_args: Vec<Robj> = args.iter().map(|v| TypeConverter::wasm_to_r_with(v, int64_mode)).collect();
+            let r = call_registered_r_function(id, r_args)?;
+            let invalid_result = |e: String| {
+                wasmer::RuntimeError::new(format!("R function for handle {} returned an invalid result: {}", id, e))
+            };
+            match result_types.len() {
+                0 => Ok(vec![]),
+                1 => Ok(vec![TypeConverter::r_to_wasm_typed(&r, result_types[0]).map_err(invalid_result)?]),
+                n => {
+                    // Multiple return values: expect R to return a list or vector with one element per result
+                    let elements = TypeConverter::split_elements(&r);
+                    if elements.len() != n {
+                        return Err(invalid_result(format!("{} values for {} results", elements.len(), n)));
                     }
+                    elements
+                        .iter()
+                        .zip(result_types.iter())
+                        .map(|(rv, ty)| TypeConverter::r_to_wasm_typed(rv, *ty).map_err(invalid_result))
+                        .collect()
                 }
-            } else {
-                Ok(vec![Value::I32(0)])
             }
         }
     );
-    ExternalPtr::new(fun)
+    Ok(ExternalPtr::new(fun))
 }
 
 /// Get a pointer to an exported table from a WASM instance by name
@@ -1380,7 +2528,7 @@ pub fn wasmer_function_new_ext(
 /// @param ptr External pointer to WasmerRuntime.
 /// @param instance_name Name of the instance.
 /// @param table_export_name Name of the exported table.
-/// @return External pointer to Table, or NULL if not found
+/// @return External pointer to Table
 /// @examples
 /// \dontrun{
 /// wasmer_get_exported_table_ext(ptr, "inst1", "table1")
@@ -1391,16 +2539,474 @@ pub fn wasmer_get_exported_table_ext(
     mut ptr: ExternalPtr<WasmerRuntime>,
     instance_name: String,
     table_export_name: String,
-) -> Option<ExternalPtr<Table>> {
+) -> WasmerResult<ExternalPtr<Table>> {
     let runtime = ptr.as_mut();
-    if let Some(instance) = runtime.instances.get(&instance_name) {
-        if let Ok(export) = instance.exports.get(&table_export_name) {
-            if let wasmer::Extern::Table(table) = export {
-                return Some(ExternalPtr::new(table.clone()));
-            }
+    let instance = lookup_instance(&runtime.instances, &instance_name)?;
+    let table = instance
+        .exports
+        .get_table(&table_export_name)
+        .map_err(|_| WasmerError::export_not_found("table", &table_export_name, &instance_name))?;
+    Ok(ExternalPtr::new(table.clone()))
+}
+
+/// Create a new WASM Global
+///
+/// @title Create WASM Global
+/// @description Create a new WASM Global holding a value of the given type.
+/// @family global operations
+/// @seealso [wasmer_global_get_ext()], [wasmer_global_set_ext()], [wasmer_get_exported_global_ext()], [wasmer_instantiate_with_globals_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param value Initial value (numeric, or NULL/Function pointer for funcref).
+/// @param value_type Value type ("i32", "i64", "f32", "f64", "funcref", "externref").
+/// @param mutable Whether the global can be set after creation.
+/// @return External pointer to Global
+/// @examples
+/// \dontrun{
+/// wasmer_global_new_ext(ptr, 1024L, "i32", TRUE)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_global_new_ext(mut ptr: ExternalPtr<WasmerRuntime>, value: Robj, value_type: String, mutable: bool) -> WasmerResult<ExternalPtr<Global>> {
+    let runtime = ptr.as_mut();
+    let ty = TypeConverter::parse_type(&value_type)
+        .map_err(WasmerError::argument)?;
+    let value = TypeConverter::r_to_wasm_typed(&value, ty)
+        .map_err(WasmerError::argument)?;
+    let global = if mutable {
+        Global::new_mut(&mut runtime.store, value)
+    } else {
+        Global::new(&mut runtime.store, value)
+    };
+    Ok(ExternalPtr::new(global))
+}
+
+/// Get the value of a WASM Global
+///
+/// @title Get WASM Global value
+/// @description Get the current value of a WASM Global. Reading a reference-typed
+/// (`funcref` or `externref`) global raises a `wasmer_argument_error` condition.
+/// @family global operations
+/// @seealso [wasmer_global_new_ext()], [wasmer_global_set_ext()], [wasmer_get_exported_global_ext()], [wasmer_instance_global_get_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param global_ptr External pointer to Global.
+/// @return Value of the global
+/// @examples
+/// \dontrun{
+/// wasmer_global_get_ext(ptr, global_ptr)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_global_get_ext(mut ptr: ExternalPtr<WasmerRuntime>, mut global_ptr: ExternalPtr<Global>) -> WasmerResult<Robj> {
+    let runtime = ptr.as_mut();
+    let global = global_ptr.as_mut();
+    TypeConverter::global_to_r(&global.get(&mut runtime.store)).map_err(WasmerError::argument)
+}
+
+/// Set the value of a mutable WASM Global
+///
+/// @title Set WASM Global value
+/// @description Set the value of a mutable WASM Global. The value is converted to the global's type.
+/// @family global operations
+/// @seealso [wasmer_global_new_ext()], [wasmer_global_get_ext()], [wasmer_get_exported_global_ext()], [wasmer_instance_global_set_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param global_ptr External pointer to Global.
+/// @param value New value.
+/// @return TRUE if successful, FALSE if the global is immutable or the value cannot be converted
+/// @examples
+/// \dontrun{
+/// wasmer_global_set_ext(ptr, global_ptr, 2048L)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_global_set_ext(mut ptr: ExternalPtr<WasmerRuntime>, mut global_ptr: ExternalPtr<Global>, value: Robj) -> bool {
+    let runtime = ptr.as_mut();
+    let global = global_ptr.as_mut();
+    set_global_value(&mut runtime.store, global, &value)
+}
+
+/// Get a pointer to an exported global from a WASM instance by name
+///
+/// @title Get exported WASM Global
+/// @description Get a pointer to an exported global from a WASM instance by name.
+/// @family global operations
+/// @seealso [wasmer_global_get_ext()], [wasmer_global_set_ext()], [wasmer_instance_global_get_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param instance_name Name of the instance.
+/// @param global_export_name Name of the exported global.
+/// @return External pointer to Global
+/// @examples
+/// \dontrun{
+/// wasmer_get_exported_global_ext(ptr, "inst1", "__stack_pointer")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_get_exported_global_ext(
+    mut ptr: ExternalPtr<WasmerRuntime>,
+    instance_name: String,
+    global_export_name: String,
+) -> WasmerResult<ExternalPtr<Global>> {
+    let runtime = ptr.as_mut();
+    let global = lookup_global(&runtime.instances, &instance_name, &global_export_name)?;
+    Ok(ExternalPtr::new(global))
+}
+
+/// Read an exported global of a WASM instance
+///
+/// @title Read exported WASM Global
+/// @description Read the value of an exported global of a WASM instance. Reading a reference-typed
+/// (`funcref` or `externref`) global raises a `wasmer_argument_error` condition.
+/// @family global operations
+/// @seealso [wasmer_instance_global_set_ext()], [wasmer_get_exported_global_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param instance_name Name of the instance.
+/// @param global_export_name Name of the exported global.
+/// @return Value of the global
+/// @examples
+/// \dontrun{
+/// wasmer_instance_global_get_ext(ptr, "inst1", "__data_end")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_instance_global_get_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, global_export_name: String) -> WasmerResult<Robj> {
+    let runtime = ptr.as_mut();
+    let global = lookup_global(&runtime.instances, &instance_name, &global_export_name)?;
+    TypeConverter::global_to_r(&global.get(&mut runtime.store)).map_err(WasmerError::argument)
+}
+
+/// Set an exported mutable global of a WASM instance
+///
+/// @title Set exported WASM Global
+/// @description Set the value of an exported mutable global of a WASM instance.
+/// @family global operations
+/// @seealso [wasmer_instance_global_get_ext()], [wasmer_get_exported_global_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param instance_name Name of the instance.
+/// @param global_export_name Name of the exported global.
+/// @param value New value.
+/// @return TRUE if successful
+/// @examples
+/// \dontrun{
+/// wasmer_instance_global_set_ext(ptr, "inst1", "__stack_pointer", 65536L)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_instance_global_set_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String, global_export_name: String, value: Robj) -> WasmerResult<bool> {
+    let runtime = ptr.as_mut();
+    let global = lookup_global(&runtime.instances, &instance_name, &global_export_name)?;
+    Ok(set_global_value(&mut runtime.store, &global, &value))
+}
+
+/// Look up an exported global of an instance by name
+fn lookup_global(instances: &HashMap<String, Instance>, instance_name: &str, global_name: &str) -> WasmerResult<Global> {
+    lookup_instance(instances, instance_name)?
+        .exports
+        .get_global(global_name)
+        .cloned()
+        .map_err(|_| WasmerError::export_not_found("global", global_name, instance_name))
+}
+
+/// Helper converting an R value to the global's type before setting it
+fn set_global_value(store: &mut Store, global: &Global, value: &Robj) -> bool {
+    let ty = global.ty(store).ty;
+    match TypeConverter::r_to_wasm_typed(value, ty) {
+        Ok(v) => global.set(store, v).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Get a handle to a compiled module
+///
+/// @title Get WASM module handle
+/// @description Get a handle to a compiled module. A handle refers to the compiled module itself
+/// rather than to its name, so it is unaffected by later compilations under the same name, and
+/// it keeps its runtime alive. Printing it shows the module's imports and exports with their types.
+/// @family module compilation
+/// @seealso [wasmer_module_instantiate_ext()], [wasmer_instance_handle_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param module_name Name of the compiled module.
+/// @return External pointer of class `wasmer_module`
+/// @examples
+/// \dontrun{
+/// mod <- wasmer_module_handle_ext(ptr, "mod1")
+/// print(mod)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_module_handle_ext(mut ptr: ExternalPtr<WasmerRuntime>, module_name: String) -> WasmerResult<Robj> {
+    let module = match ptr.as_mut().modules.get(&module_name) {
+        Some(module) => module.clone(),
+        None => return Err(WasmerError::not_found("module", &module_name)),
+    };
+    Ok(WasmerHandle::new(ptr.clone(), &module_name, None, HandleObject::Module(module)).into_robj())
+}
+
+/// Instantiate a module handle and return a handle to the new instance
+///
+/// @title Instantiate WASM module handle
+/// @description Instantiate the module a handle refers to, register the instance under
+/// `instance_name` and return a handle to it. Imports are resolved as in [wasmer_instantiate_ext()],
+/// plus the entries of `imports` given as for [wasmer_instantiate_with_imports_ext()].
+/// Instance names cannot be reused: remove the old instance with [wasmer_instance_remove_ext()] first.
+/// @family module instantiation
+/// @seealso [wasmer_module_handle_ext()], [wasmer_function_handle_ext()], [wasmer_memory_handle_ext()]
+/// @param module Module handle from [wasmer_module_handle_ext()].
+/// @param instance_name Name to register the instance under.
+/// @param imports Optional named list of namespaces, each a named list of external pointers or handles.
+/// @return External pointer of class `wasmer_instance`
+/// @examples
+/// \dontrun{
+/// inst <- wasmer_module_instantiate_ext(wasmer_module_handle_ext(ptr, "mod1"), "inst1")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_module_instantiate_ext(module: ExternalPtr<WasmerHandle>, instance_name: String, #[default = "NULL"] imports: Nullable<List>) -> WasmerResult<Robj> {
+    let module = module.as_ref();
+    let compiled = module.module()?.clone();
+    let mut runtime_ptr = module.runtime()?;
+    let runtime = runtime_ptr.as_mut();
+    let env = FunctionEnv::new(&mut runtime.store, WasmerEnv { instance: None });
+    let mut import_object = imports! {
+        "env" => {
+            "r_host_call" => create_generic_r_host_function(&env, &mut runtime.store),
         }
+    };
+    if let NotNull(imports) = imports {
+        let user_imports = ImportUtils::imports_from_list(&imports)
+            .map_err(|e| WasmerError::argument(format!("Error building imports: {}", e)))?;
+        import_object.extend(&user_imports);
     }
-    None
+    let instance = instantiate_compiled(runtime, &compiled, module.name(), &instance_name, import_object)?;
+    env.as_mut(&mut runtime.store).instance = Some(instance.clone());
+    runtime.env = Some(env);
+    let wasi = runtime.wasi_instances.get(&instance_name).cloned();
+    Ok(WasmerHandle::new(runtime_ptr, &instance_name, None, HandleObject::Instance(instance))
+        .with_wasi(wasi)
+        .into_robj())
+}
+
+/// Get a handle to an instance
+///
+/// @title Get WASM instance handle
+/// @description Get a handle to a registered instance. The handle stays valid when the name is
+/// removed or reused, and keeps its runtime alive. Printing it shows the instance's exports with their types.
+/// @family module instantiation
+/// @seealso [wasmer_function_handle_ext()], [wasmer_memory_handle_ext()], [wasmer_instance_remove_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param instance_name Name of the instance.
+/// @return External pointer of class `wasmer_instance`
+/// @examples
+/// \dontrun{
+/// inst <- wasmer_instance_handle_ext(ptr, "inst1")
+/// print(inst)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_instance_handle_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String) -> WasmerResult<Robj> {
+    let runtime = ptr.as_mut();
+    let instance = lookup_instance(&runtime.instances, &instance_name)?.clone();
+    let wasi = runtime.wasi_instances.get(&instance_name).cloned();
+    Ok(WasmerHandle::new(ptr.clone(), &instance_name, None, HandleObject::Instance(instance))
+        .with_wasi(wasi)
+        .into_robj())
+}
+
+/// Remove an instance from the runtime so that its name can be reused
+///
+/// @title Remove WASM instance
+/// @description Remove a registered instance from the runtime so that its name can be reused.
+/// Handles to the instance or its exports remain usable, as does the WASI environment attached
+/// to instance handles.
+/// @family module instantiation
+/// @seealso [wasmer_instantiate_ext()], [wasmer_instance_handle_ext()]
+/// @param ptr External pointer to WasmerRuntime.
+/// @param instance_name Name of the instance.
+/// @return TRUE if an instance was removed, FALSE if no instance had this name
+/// @examples
+/// \dontrun{
+/// wasmer_instance_remove_ext(ptr, "inst1")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_instance_remove_ext(mut ptr: ExternalPtr<WasmerRuntime>, instance_name: String) -> bool {
+    let runtime = ptr.as_mut();
+    runtime.wasi_instances.remove(&instance_name);
+    runtime.instances.remove(&instance_name).is_some()
+}
+
+/// Get a handle to a function exported by an instance
+///
+/// @title Get WASM function handle
+/// @description Get a handle to an exported function. The handle can be called with
+/// [wasmer_function_call_ext()] and [wasmer_function_call_vectorized_ext()], and used as an import.
+/// Printing it shows the function's signature.
+/// @family function calling
+/// @seealso [wasmer_instance_handle_ext()], [wasmer_call_function_ext()]
+/// @param instance Instance handle from [wasmer_instance_handle_ext()] or [wasmer_module_instantiate_ext()].
+/// @param function_name Name of the exported function.
+/// @return External pointer of class `wasmer_function`
+/// @examples
+/// \dontrun{
+/// add <- wasmer_function_handle_ext(inst, "add")
+/// wasmer_function_call_ext(add, list(1, 2))
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_function_handle_ext(instance: ExternalPtr<WasmerHandle>, function_name: String) -> WasmerResult<Robj> {
+    let instance = instance.as_ref();
+    let func = instance
+        .instance()?
+        .exports
+        .get_function(&function_name)
+        .map_err(|_| WasmerError::export_not_found("function", &function_name, instance.name()))?
+        .clone();
+    let owner = instance.instance()?.clone();
+    let runtime = instance.runtime()?;
+    Ok(WasmerHandle::new(runtime, &function_name, Some(instance.name()), HandleObject::Function(func, owner)).into_robj())
+}
+
+/// Get a handle to a memory exported by an instance
+///
+/// @title Get WASM memory handle
+/// @description Get a handle to an exported memory, usable with [wasmer_memory_handle_read_ext()]
+/// and [wasmer_memory_handle_write_ext()] and as an import. Printing it shows the memory's limits and current size.
+/// @family memory operations
+/// @seealso [wasmer_instance_handle_ext()], [wasmer_memory_read_ext()]
+/// @param instance Instance handle from [wasmer_instance_handle_ext()] or [wasmer_module_instantiate_ext()].
+/// @param memory_name Name of the exported memory.
+/// @return External pointer of class `wasmer_memory`
+/// @examples
+/// \dontrun{
+/// mem <- wasmer_memory_handle_ext(inst, "memory")
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_memory_handle_ext(instance: ExternalPtr<WasmerHandle>, memory_name: String) -> WasmerResult<Robj> {
+    let instance = instance.as_ref();
+    let memory = instance
+        .instance()?
+        .exports
+        .get_memory(&memory_name)
+        .map_err(|_| WasmerError::export_not_found("memory", &memory_name, instance.name()))?
+        .clone();
+    let runtime = instance.runtime()?;
+    Ok(WasmerHandle::new(runtime, &memory_name, Some(instance.name()), HandleObject::Memory(memory)).into_robj())
+}
+
+/// Call a function handle
+///
+/// @title Call WASM function handle
+/// @description Call the function a handle refers to. Arguments, results and errors are handled
+/// as in [wasmer_call_function_ext()].
+/// @family function calling
+/// @seealso [wasmer_function_handle_ext()], [wasmer_function_call_vectorized_ext()]
+/// @param func Function handle from [wasmer_function_handle_ext()].
+/// @param args Arguments as R list.
+/// @return The function result: a single value, or a list for multiple results
+/// @examples
+/// \dontrun{
+/// wasmer_function_call_ext(add, list(1, 2))
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_function_call_ext(func: ExternalPtr<WasmerHandle>, args: List) -> WasmerResult<Robj> {
+    let func = func.as_ref();
+    let (function, instance) = func.function()?;
+    let mut runtime_ptr = func.runtime()?;
+    guarded_call(runtime_ptr.as_mut(), instance, |store, int64_mode| {
+        call_function(store, function, func.instance_name(), func.name(), &args, int64_mode)
+    })
+}
+
+/// Call a function handle element-wise over R vectors
+///
+/// @title Call WASM function handle over vectors
+/// @description Call the function a handle refers to once per element of the argument vectors,
+/// as in [wasmer_call_function_vectorized_ext()].
+/// @family function calling
+/// @seealso [wasmer_function_handle_ext()], [wasmer_function_call_ext()]
+/// @param func Function handle from [wasmer_function_handle_ext()].
+/// @param args List or data frame of argument vectors, one per parameter.
+/// @return A vector, a data frame for multi-value functions, or `NULL` when the function has no results
+/// @examples
+/// \dontrun{
+/// wasmer_function_call_vectorized_ext(add, list(1:10, 5L))
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_function_call_vectorized_ext(func: ExternalPtr<WasmerHandle>, args: List) -> WasmerResult<Robj> {
+    let func = func.as_ref();
+    let (function, instance) = func.function()?;
+    let mut runtime_ptr = func.runtime()?;
+    guarded_call(runtime_ptr.as_mut(), instance, |store, int64_mode| {
+        VectorizedCall::call(store, function, &args, func.instance_name(), func.name(), int64_mode)
+    })
+}
+
+/// Read bytes from a memory handle
+///
+/// @title Read WASM memory handle
+/// @description Read bytes from the memory a handle refers to.
+/// @family memory operations
+/// @seealso [wasmer_memory_handle_ext()], [wasmer_memory_handle_write_ext()]
+/// @param memory Memory handle from [wasmer_memory_handle_ext()].
+/// @param offset Offset to start reading.
+/// @param length Number of bytes to read.
+/// @return Raw vector of bytes
+/// @examples
+/// \dontrun{
+/// wasmer_memory_handle_read_ext(mem, 0, 10)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_memory_handle_read_ext(memory: ExternalPtr<WasmerHandle>, offset: i32, length: i32) -> WasmerResult<Robj> {
+    let memory = memory.as_ref();
+    let wasm_memory = memory.memory()?;
+    let runtime_ptr = memory.runtime()?;
+    Ok(r!(read_memory_bytes(&runtime_ptr.as_ref().store, wasm_memory, offset, length)?))
+}
+
+/// Write bytes to a memory handle
+///
+/// @title Write WASM memory handle
+/// @description Write bytes to the memory a handle refers to.
+/// @family memory operations
+/// @seealso [wasmer_memory_handle_ext()], [wasmer_memory_handle_read_ext()]
+/// @param memory Memory handle from [wasmer_memory_handle_ext()].
+/// @param offset Non-negative offset to start writing.
+/// @param bytes Raw vector of bytes to write.
+/// @return TRUE if successful, FALSE if the bytes do not fit in the memory
+/// @examples
+/// \dontrun{
+/// wasmer_memory_handle_write_ext(mem, 0, as.raw(c(1, 2, 3)))
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_memory_handle_write_ext(memory: ExternalPtr<WasmerHandle>, offset: i32, bytes: Robj) -> WasmerResult<bool> {
+    let memory = memory.as_ref();
+    let wasm_memory = memory.memory()?;
+    let runtime_ptr = memory.runtime()?;
+    write_memory_bytes(&runtime_ptr.as_ref().store, wasm_memory, offset, &bytes)
+}
+
+/// Describe a handle
+///
+/// @title Describe WASM handle
+/// @description Describe a module, instance, function or memory handle with its type information.
+/// This is what `format()` and `print()` show for handles.
+/// @family exports and signatures
+/// @seealso [wasmer_module_handle_ext()], [wasmer_instance_handle_ext()], [wasmer_function_handle_ext()], [wasmer_memory_handle_ext()]
+/// @param handle Handle of class `wasmer_handle`.
+/// @return Character vector, one line per element
+/// @examples
+/// \dontrun{
+/// wasmer_handle_describe_ext(add)
+/// }
+/// @export
+#[extendr]
+pub fn wasmer_handle_describe_ext(handle: ExternalPtr<WasmerHandle>) -> WasmerResult<Vec<String>> {
+    let handle = handle.as_ref();
+    let runtime_ptr = handle.runtime()?;
+    Ok(handle.describe(&runtime_ptr.as_ref().store))
 }
 
 // Macro to generate static R host function wrappers for common signatures
@@ -1426,13 +3032,9 @@ macro_rules! impl_r_host_function {
         ) -> ExternalPtr<Function> {
             let runtime = ptr.as_mut();
             let id = register_r_function_internal(rfun);
-            let fun = Function::new_typed(&mut runtime.store, move |x: i32| -> i32 {
-                let result = R_FUNCTION_REGISTRY.with(|reg| {
-                    reg.borrow().get(&id).cloned()
-                }).and_then(|rfun| {
-                    rfun.call(pairlist!(r!(x))).ok()
-                });
-                result.and_then(|r| r.as_integer()).unwrap_or(0)
+            let fun = Function::new_typed(&mut runtime.store, move |x: i32| -> std::result::Result<i32, wasmer::RuntimeError> {
+                let r = call_registered_r_function(id, vec![r!(x)])?;
+                r.as_integer().ok_or_else(|| host_result_error(id, "an integer", &r))
             });
             ExternalPtr::new(fun)
         }
@@ -1457,13 +3059,9 @@ macro_rules! impl_r_host_function {
         ) -> ExternalPtr<Function> {
             let runtime = ptr.as_mut();
             let id = register_r_function_internal(rfun);
-            let fun = Function::new_typed(&mut runtime.store, move |x: i32, y: i32| -> i32 {
-                let result = R_FUNCTION_REGISTRY.with(|reg| {
-                    reg.borrow().get(&id).cloned()
-                }).and_then(|rfun| {
-                    rfun.call(pairlist!(r!(x), r!(y))).ok()
-                });
-                result.and_then(|r| r.as_integer()).unwrap_or(0)
+            let fun = Function::new_typed(&mut runtime.store, move |x: i32, y: i32| -> std::result::Result<i32, wasmer::RuntimeError> {
+                let r = call_registered_r_function(id, vec![r!(x), r!(y)])?;
+                r.as_integer().ok_or_else(|| host_result_error(id, "an integer", &r))
             });
             ExternalPtr::new(fun)
         }
@@ -1488,13 +3086,9 @@ macro_rules! impl_r_host_function {
         ) -> ExternalPtr<Function> {
             let runtime = ptr.as_mut();
             let id = register_r_function_internal(rfun);
-            let fun = Function::new_typed(&mut runtime.store, move |x: f64, y: f64| -> f64 {
-                let result = R_FUNCTION_REGISTRY.with(|reg| {
-                    reg.borrow().get(&id).cloned()
-                }).and_then(|rfun| {
-                    rfun.call(pairlist!(r!(x), r!(y))).ok()
-                });
-                result.and_then(|r| r.as_real()).unwrap_or(0.0)
+            let fun = Function::new_typed(&mut runtime.store, move |x: f64, y: f64| -> std::result::Result<f64, wasmer::RuntimeError> {
+                let r = call_registered_r_function(id, vec![r!(x), r!(y)])?;
+                r.as_real().ok_or_else(|| host_result_error(id, "a number", &r))
             });
             ExternalPtr::new(fun)
         }
@@ -1519,13 +3113,9 @@ macro_rules! impl_r_host_function {
         ) -> ExternalPtr<Function> {
             let runtime = ptr.as_mut();
             let id = register_r_function_internal(rfun);
-            let fun = Function::new_typed(&mut runtime.store, move |x: f64| -> f64 {
-                let result = R_FUNCTION_REGISTRY.with(|reg| {
-                    reg.borrow().get(&id).cloned()
-                }).and_then(|rfun| {
-                    rfun.call(pairlist!(r!(x))).ok()
-                });
-                result.and_then(|r| r.as_real()).unwrap_or(0.0)
+            let fun = Function::new_typed(&mut runtime.store, move |x: f64| -> std::result::Result<f64, wasmer::RuntimeError> {
+                let r = call_registered_r_function(id, vec![r!(x)])?;
+                r.as_real().ok_or_else(|| host_result_error(id, "a number", &r))
             });
             ExternalPtr::new(fun)
         }
@@ -1550,12 +3140,9 @@ macro_rules! impl_r_host_function {
         ) -> ExternalPtr<Function> {
             let runtime = ptr.as_mut();
             let id = register_r_function_internal(rfun);
-            let fun = Function::new_typed(&mut runtime.store, move |x: i32| {
-                R_FUNCTION_REGISTRY.with(|reg| {
-                    reg.borrow().get(&id).cloned()
-                }).and_then(|rfun| {
-                    rfun.call(pairlist!(r!(x))).ok()
-                });
+            let fun = Function::new_typed(&mut runtime.store, move |x: i32| -> std::result::Result<(), wasmer::RuntimeError> {
+                call_registered_r_function(id, vec![r!(x)])?;
+                Ok(())
             });
             ExternalPtr::new(fun)
         }
@@ -1580,13 +3167,9 @@ macro_rules! impl_r_host_function {
         ) -> ExternalPtr<Function> {
             let runtime = ptr.as_mut();
             let id = register_r_function_internal(rfun);
-            let fun = Function::new_typed(&mut runtime.store, move || -> i32 {
-                let result = R_FUNCTION_REGISTRY.with(|reg| {
-                    reg.borrow().get(&id).cloned()
-                }).and_then(|rfun| {
-                    rfun.call(pairlist!()).ok()
-                });
-                result.and_then(|r| r.as_integer()).unwrap_or(0)
+            let fun = Function::new_typed(&mut runtime.store, move || -> std::result::Result<i32, wasmer::RuntimeError> {
+                let r = call_registered_r_function(id, vec![])?;
+                r.as_integer().ok_or_else(|| host_result_error(id, "an integer", &r))
             });
             ExternalPtr::new(fun)
         }
@@ -1610,8 +3193,20 @@ extendr_module! {
     fn wasmer_runtime_new;
     fn wasmer_compile_wat_ext;
     fn wasmer_compile_wasm_ext;
+    fn wasmer_validate_ext;
+    fn wasmer_module_serialize_ext;
+    fn wasmer_module_deserialize_ext;
+    fn wasmer_runtime_set_cache_dir_ext;
+    fn wasmer_runtime_set_timeout_ext;
+    fn wasmer_runtime_interrupt_ext;
+    fn wasmer_compiler_features_ext;
+    fn wasmer_runtime_features_ext;
+    fn wasmer_runtime_target_ext;
     fn wasmer_instantiate_ext;
     fn wasmer_call_function_ext;
+    fn wasmer_call_function_vectorized_ext;
+    fn wasmer_fuel_remaining_ext;
+    fn wasmer_fuel_set_ext;
     fn wasmer_list_exports_ext;
     fn wasmer_register_r_function_ext;
     fn wasmer_math_example_ext;
@@ -1621,17 +3216,29 @@ extendr_module! {
     fn wasmer_call_function_safe_ext;
     fn wasmer_host_function_example_ext;
     fn wasmer_list_function_signatures_ext;
+    fn wasmer_module_imports_ext;
+    fn wasmer_module_exports_ext;
+    fn wasmer_module_custom_sections_ext;
+    fn wasmer_module_wasi_abi_ext;
     fn wasmer_memory_size_ext;
     fn wasmer_memory_read_ext;
     fn wasmer_memory_write_ext;
     fn wasmer_memory_read_string_ext;
     fn wasmer_memory_grow_ext;
+    fn wasmer_memory_new_ext;
+    fn wasmer_get_exported_memory_ext;
     fn wasmer_table_new_ext;
     fn wasmer_table_set_ext;
     fn wasmer_table_grow_ext;
     fn wasmer_table_get_ext;
     fn wasmer_function_new_ext;
     fn wasmer_get_exported_table_ext;
+    fn wasmer_global_new_ext;
+    fn wasmer_global_get_ext;
+    fn wasmer_global_set_ext;
+    fn wasmer_get_exported_global_ext;
+    fn wasmer_instance_global_get_ext;
+    fn wasmer_instance_global_set_ext;
     fn wasmer_function_new_i32_to_i32;
     fn wasmer_function_new_i32_i32_to_i32;
     fn wasmer_function_new_f64_f64_to_f64;
@@ -1639,7 +3246,28 @@ extendr_module! {
     fn wasmer_function_new_i32_to_void;
     fn wasmer_function_new_void_to_i32;
     fn wasmer_runtime_new_with_compiler_ext;
+    fn wasmer_runtime_new_headless_ext;
     fn wasmer_instantiate_with_table_ext;
+    fn wasmer_instantiate_with_globals_ext;
+    fn wasmer_instantiate_with_imports_ext;
+    fn wasmer_register_instance_ext;
+    fn wasmer_unregister_namespace_ext;
     fn wasmer_wasi_state_new_ext;
+    fn wasmer_wasi_output_ext;
+    fn wasmer_wasi_files_ext;
+    fn wasmer_wasi_run_ext;
+    fn wasmer_instance_wasi_output_ext;
+    fn wasmer_instance_wasi_files_ext;
     fn wasmer_runtime_release_ressources;
+    fn wasmer_module_handle_ext;
+    fn wasmer_module_instantiate_ext;
+    fn wasmer_instance_handle_ext;
+    fn wasmer_instance_remove_ext;
+    fn wasmer_function_handle_ext;
+    fn wasmer_memory_handle_ext;
+    fn wasmer_function_call_ext;
+    fn wasmer_function_call_vectorized_ext;
+    fn wasmer_memory_handle_read_ext;
+    fn wasmer_memory_handle_write_ext;
+    fn wasmer_handle_describe_ext;
 }
@@ -0,0 +1,105 @@
+use std::ptr::NonNull;
+use wasmer::sys::vm::{VMConfig, VMMemory, VMMemoryDefinition, VMTable, VMTableDefinition};
+use wasmer::sys::{BaseTunables, Target, Tunables};
+use wasmer_types::{MemoryError, MemoryStyle, MemoryType, Pages, TableStyle, TableType};
+use crate::runtime_options::RuntimeOptions;
+
+/// Engine tunables enforcing the resource limits of a runtime.
+///
+/// Memories and tables are created with their maximum lowered to the limit, so that growing past
+/// it fails like growing past a declared maximum, and creating one whose minimum exceeds the limit
+/// fails outright. The stack size applies to every call into wasm.
+pub struct ResourceLimits {
+    base: BaseTunables,
+    max_memory_pages: Option<Pages>,
+    max_table_elements: Option<u32>,
+    vmconfig: VMConfig,
+}
+
+impl ResourceLimits {
+    /// Tunables for the limits set in `options`, or `None` when there are none
+    pub fn from_options(options: &RuntimeOptions, target: &Target) -> Option<Self> {
+        if options.max_memory_pages.is_none() && options.max_table_elements.is_none() && options.stack_size.is_none() {
+            return None;
+        }
+        Some(Self {
+            base: BaseTunables::for_target(target),
+            max_memory_pages: options.max_memory_pages.map(Pages),
+            max_table_elements: options.max_table_elements,
+            vmconfig: VMConfig { wasm_stack_size: options.stack_size },
+        })
+    }
+
+    fn limit_memory(&self, ty: &MemoryType) -> Result<MemoryType, MemoryError> {
+        let Some(limit) = self.max_memory_pages else {
+            return Ok(*ty);
+        };
+        if ty.minimum > limit {
+            return Err(MemoryError::Generic(format!(
+                "memory of {} pages exceeds the runtime limit of {} pages (max_memory_pages)",
+                ty.minimum.0, limit.0
+            )));
+        }
+        let mut limited = *ty;
+        limited.maximum = Some(ty.maximum.map_or(limit, |maximum| maximum.min(limit)));
+        Ok(limited)
+    }
+
+    fn limit_table(&self, ty: &TableType) -> Result<TableType, String> {
+        let Some(limit) = self.max_table_elements else {
+            return Ok(*ty);
+        };
+        if ty.minimum > limit {
+            return Err(format!(
+                "table of {} elements exceeds the runtime limit of {} elements (max_table_elements)",
+                ty.minimum, limit
+            ));
+        }
+        let mut limited = *ty;
+        limited.maximum = Some(ty.maximum.map_or(limit, |maximum| maximum.min(limit)));
+        Ok(limited)
+    }
+}
+
+impl Tunables for ResourceLimits {
+    fn memory_style(&self, memory: &MemoryType) -> MemoryStyle {
+        match self.limit_memory(memory) {
+            Ok(limited) => self.base.memory_style(&limited),
+            Err(_) => self.base.memory_style(memory),
+        }
+    }
+
+    fn table_style(&self, table: &TableType) -> TableStyle {
+        self.base.table_style(table)
+    }
+
+    fn create_host_memory(&self, ty: &MemoryType, style: &MemoryStyle) -> Result<VMMemory, MemoryError> {
+        self.base.create_host_memory(&self.limit_memory(ty)?, style)
+    }
+
+    unsafe fn create_vm_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+        vm_definition_location: NonNull<VMMemoryDefinition>,
+    ) -> Result<VMMemory, MemoryError> {
+        self.base.create_vm_memory(&self.limit_memory(ty)?, style, vm_definition_location)
+    }
+
+    fn create_host_table(&self, ty: &TableType, style: &TableStyle) -> Result<VMTable, String> {
+        self.base.create_host_table(&self.limit_table(ty)?, style)
+    }
+
+    unsafe fn create_vm_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+        vm_definition_location: NonNull<VMTableDefinition>,
+    ) -> Result<VMTable, String> {
+        self.base.create_vm_table(&self.limit_table(ty)?, style, vm_definition_location)
+    }
+
+    fn vmconfig(&self) -> &VMConfig {
+        &self.vmconfig
+    }
+}
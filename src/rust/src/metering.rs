@@ -0,0 +1,92 @@
+use std::sync::{Arc, Mutex};
+use wasmer::sys::{FunctionMiddleware, MiddlewareError, ModuleMiddleware};
+use wasmer::wasmparser::Operator;
+use wasmer::{AsStoreMut, Instance};
+use wasmer_middlewares::metering::{get_remaining_points, set_remaining_points, MeteringPoints};
+use wasmer_middlewares::Metering;
+use wasmer_types::{LocalFunctionIndex, ModuleInfo};
+
+/// Globals added by the metering middleware to every module it instruments
+pub const REMAINING_POINTS_GLOBAL: &str = "wasmer_metering_remaining_points";
+pub const POINTS_EXHAUSTED_GLOBAL: &str = "wasmer_metering_points_exhausted";
+
+/// Most fuel an instance can hold, as the remaining points are kept in an `i64` global
+pub const MAX_FUEL: u64 = i64::MAX as u64;
+
+/// Cost of an operator: every executed operator costs one point
+type CostFunction = fn(&Operator) -> u64;
+
+fn unit_cost(_operator: &Operator) -> u64 {
+    1
+}
+
+/// Fuel metering of wasm code with the `wasmer-middlewares` metering: every executed operator
+/// costs one point and a call traps once the instance has no points left
+pub struct FuelMeter;
+
+impl FuelMeter {
+    /// Middleware to push on the compiler config; `initial_fuel` is what new instances start with
+    pub fn middleware(initial_fuel: u64) -> Arc<dyn ModuleMiddleware> {
+        Arc::new(FuelMiddleware {
+            initial_fuel,
+            current: Mutex::new(None),
+        })
+    }
+
+    /// Whether the instance was compiled with metering
+    pub fn is_metered(instance: &Instance) -> bool {
+        instance.exports.get_global(REMAINING_POINTS_GLOBAL).is_ok()
+            && instance.exports.get_global(POINTS_EXHAUSTED_GLOBAL).is_ok()
+    }
+
+    /// Remaining fuel of a metered instance, zero once exhausted
+    pub fn remaining(store: &mut impl AsStoreMut, instance: &Instance) -> Option<u64> {
+        if !Self::is_metered(instance) {
+            return None;
+        }
+        match get_remaining_points(store, instance) {
+            MeteringPoints::Remaining(points) => Some(points),
+            MeteringPoints::Exhausted => Some(0),
+        }
+    }
+
+    /// Whether a metered instance ran out of fuel
+    pub fn is_exhausted(store: &mut impl AsStoreMut, instance: &Instance) -> bool {
+        Self::is_metered(instance) && get_remaining_points(store, instance) == MeteringPoints::Exhausted
+    }
+
+    /// Set the fuel of a metered instance, clearing the exhausted state
+    pub fn refill(store: &mut impl AsStoreMut, instance: &Instance, points: u64) -> std::result::Result<(), String> {
+        if !Self::is_metered(instance) {
+            return Err(
+                "The instance was not compiled with fuel metering; create the runtime with the 'fuel' option".to_string(),
+            );
+        }
+        set_remaining_points(store, instance, points);
+        Ok(())
+    }
+}
+
+/// Module middleware handing every module to a fresh [`Metering`]. The engine of a runtime
+/// compiles all of its modules, one at a time, with the same middleware, while a `Metering`
+/// can only instrument a single module.
+#[derive(Debug)]
+struct FuelMiddleware {
+    initial_fuel: u64,
+    current: Mutex<Option<Arc<Metering<CostFunction>>>>,
+}
+
+impl ModuleMiddleware for FuelMiddleware {
+    fn generate_function_middleware(&self, local_function_index: LocalFunctionIndex) -> Box<dyn FunctionMiddleware> {
+        let current = self.current.lock().unwrap();
+        let metering = current.as_ref().expect("module info is transformed before functions are compiled");
+        metering.generate_function_middleware(local_function_index)
+    }
+
+    fn transform_module_info(&self, info: &mut ModuleInfo) -> std::result::Result<(), MiddlewareError> {
+        let metering = Metering::new(self.initial_fuel, unit_cost as CostFunction);
+        metering.transform_module_info(info)?;
+        *self.current.lock().unwrap() = Some(Arc::new(metering));
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use wasmer::{Module, Store};
+use crate::target::EngineTarget;
+
+/// Magic bytes starting every artifact serialized by this package
+const ARTIFACT_MAGIC: &[u8; 8] = b"RWASMER1";
+
+/// Compiler name of runtimes without a compiler, which load artifacts produced by any compiler
+pub const HEADLESS_COMPILER: &str = "headless";
+
+/// What compiled code depends on besides the wasm binary
+#[derive(Debug, Clone)]
+pub struct EngineSignature {
+    pub compiler: String,
+    /// Triple and CPU features, as given by `EngineTarget::describe`
+    pub target: String,
+    /// Engine options changing the generated code, e.g. `fuel=1000`; empty for the defaults
+    pub config: String,
+}
+
+/// Serialized modules and the on-disk cache of compiled artifacts.
+///
+/// An artifact is the wasmer serialization of a module preceded by a small header recording
+/// the engine signature and wasmer version it was produced with, so that loading it into an
+/// incompatible runtime fails with a clear message instead of a generic one. Artifacts hold
+/// native code, so they and the cache directory must be as trusted as the package itself.
+pub struct ModuleCache {
+    dir: PathBuf,
+}
+
+impl ModuleCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Serialize a module together with its compatibility header. A headless runtime does not
+    /// know which compiler produced its modules, so it cannot write a header for them.
+    pub fn serialize(module: &Module, engine: &EngineSignature) -> std::result::Result<Vec<u8>, String> {
+        if engine.compiler == HEADLESS_COMPILER {
+            return Err(
+                "A headless runtime cannot serialize modules; serialize them with the runtime that compiled them".to_string(),
+            );
+        }
+        let body = module
+            .serialize()
+            .map_err(|e| format!("Failed to serialize module: {}", e))?;
+        let header = format!("{}\n{}\n{}\n{}", engine.compiler, engine.target, engine.config, wasmer_types::VERSION);
+        let mut bytes = Vec::with_capacity(ARTIFACT_MAGIC.len() + 4 + header.len() + body.len());
+        bytes.extend_from_slice(ARTIFACT_MAGIC);
+        bytes.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        bytes.extend_from_slice(&body);
+        Ok(bytes)
+    }
+
+    /// Deserialize an artifact made by [`ModuleCache::serialize`], checking that it was produced
+    /// by an engine with the same signature and the same wasmer version as the runtime loading it.
+    /// The runtime may target more CPU features than the artifact uses, and a headless runtime
+    /// accepts artifacts of every compiler.
+    pub fn deserialize(store: &Store, bytes: &[u8], engine: &EngineSignature) -> std::result::Result<Module, String> {
+        let (header, body) = Self::split_artifact(bytes)?;
+        let fields: Vec<&str> = header.split('\n').collect();
+        let [artifact_compiler, artifact_target, artifact_config, artifact_version] = fields.as_slice() else {
+            return Err("Malformed artifact header".to_string());
+        };
+        if engine.compiler != HEADLESS_COMPILER && *artifact_compiler != engine.compiler {
+            return Err(format!(
+                "Artifact was compiled with '{}' but the runtime uses '{}'",
+                artifact_compiler, engine.compiler
+            ));
+        }
+        EngineTarget::check_artifact(artifact_target, &engine.target)?;
+        if *artifact_config != engine.config {
+            let describe = |config: &str| if config.is_empty() { "default".to_string() } else { format!("'{}'", config) };
+            return Err(format!(
+                "Artifact was compiled with {} engine options but the runtime uses {} options",
+                describe(artifact_config),
+                describe(&engine.config)
+            ));
+        }
+        if *artifact_version != wasmer_types::VERSION {
+            return Err(format!(
+                "Artifact was produced by wasmer {} but this package uses wasmer {}",
+                artifact_version,
+                wasmer_types::VERSION
+            ));
+        }
+        // The artifact's native code is loaded as is, so this is unsafe for artifacts that do not
+        // come from a trusted source.
+        unsafe { Module::deserialize(store, body) }
+            .map_err(|e| format!("Failed to deserialize module: {}", e))
+    }
+
+    fn split_artifact(bytes: &[u8]) -> std::result::Result<(&str, &[u8]), String> {
+        let not_an_artifact = || "Not a serialized wasmer module".to_string();
+        let rest = bytes.strip_prefix(ARTIFACT_MAGIC.as_slice()).ok_or_else(not_an_artifact)?;
+        if rest.len() < 4 {
+            return Err(not_an_artifact());
+        }
+        let header_len = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let rest = &rest[4..];
+        if rest.len() < header_len {
+            return Err(not_an_artifact());
+        }
+        let header = std::str::from_utf8(&rest[..header_len]).map_err(|_| not_an_artifact())?;
+        Ok((header, &rest[header_len..]))
+    }
+
+    /// Cache file for a wasm binary: `<dir>/<compiler>/<blake3 of wasm, target, options and wasmer version>.wasmu`
+    pub fn path_for(&self, wasm_bytes: &[u8], engine: &EngineSignature) -> PathBuf {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(wasm_bytes);
+        for part in [engine.target.as_str(), engine.config.as_str(), wasmer_types::VERSION] {
+            hasher.update(b"\0");
+            hasher.update(part.as_bytes());
+        }
+        self.dir
+            .join(&engine.compiler)
+            .join(format!("{}.wasmu", hasher.finalize().to_hex()))
+    }
+
+    /// Load the cached artifact for a wasm binary, if any. Unreadable or incompatible
+    /// entries are treated as missing and will be overwritten by [`ModuleCache::store`].
+    pub fn load(&self, store: &Store, wasm_bytes: &[u8], engine: &EngineSignature) -> Option<Module> {
+        let bytes = fs::read(self.path_for(wasm_bytes, engine)).ok()?;
+        Self::deserialize(store, &bytes, engine).ok()
+    }
+
+    /// Create the cache directories, accessible to the current user only where supported, so that
+    /// other users cannot plant artifacts in them
+    fn create_dir(dir: &Path) -> std::io::Result<()> {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder.create(dir)
+    }
+
+    /// Write the artifact for a wasm binary, via a temporary file so readers never see partial entries
+    pub fn store(&self, module: &Module, wasm_bytes: &[u8], engine: &EngineSignature) -> std::result::Result<PathBuf, String> {
+        let path = self.path_for(wasm_bytes, engine);
+        let parent = path.parent().expect("cache entries live in a compiler directory");
+        Self::create_dir(parent).map_err(|e| format!("Cannot create cache directory '{}': {}", parent.display(), e))?;
+        let bytes = Self::serialize(module, engine)?;
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp, bytes).map_err(|e| format!("Cannot write cache entry '{}': {}", tmp.display(), e))?;
+        fs::rename(&tmp, &path).map_err(|e| format!("Cannot write cache entry '{}': {}", path.display(), e))?;
+        Ok(path)
+    }
+}
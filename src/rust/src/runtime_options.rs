@@ -0,0 +1,169 @@
+use extendr_api::prelude::*;
+use std::time::Duration;
+use crate::features::EngineFeatures;
+use crate::metering::MAX_FUEL;
+
+/// Options of `wasmer_runtime_new_with_compiler_ext()`, given in R as a named list
+#[derive(Debug, Clone)]
+pub struct RuntimeOptions {
+    /// Fuel each instance starts with; enables metering when set
+    pub fuel: Option<u64>,
+    /// Refill the fuel to `fuel` before every call instead of once per instance
+    pub fuel_per_call: bool,
+    /// Compile modules with the checks that let calls be stopped by timeouts and user interrupts;
+    /// on unless turned off for maximum speed, since watching each call costs a thread
+    pub interruptible: bool,
+    /// Wall-clock limit of each call
+    pub timeout: Option<Duration>,
+    /// Largest size, in 64 KiB pages, any memory of the runtime may have
+    pub max_memory_pages: Option<u32>,
+    /// Largest number of elements any table of the runtime may have
+    pub max_table_elements: Option<u32>,
+    /// Stack size in bytes available to wasm code
+    pub stack_size: Option<usize>,
+    /// Proposals enabled or disabled on top of the compiler's defaults, sorted by name
+    pub features: Vec<(String, bool)>,
+    /// Target triple to compile for instead of the host
+    pub target: Option<String>,
+    /// CPU features to add (`"avx2"`) or remove (`"-avx2"`)
+    pub cpu_features: Vec<String>,
+}
+
+impl Default for RuntimeOptions {
+    fn default() -> Self {
+        Self {
+            fuel: None,
+            fuel_per_call: false,
+            interruptible: true,
+            timeout: None,
+            max_memory_pages: None,
+            max_table_elements: None,
+            stack_size: None,
+            features: Vec::new(),
+            target: None,
+            cpu_features: Vec::new(),
+        }
+    }
+}
+
+impl RuntimeOptions {
+    pub fn from_list(options: &List) -> std::result::Result<Self, String> {
+        let mut parsed = RuntimeOptions::default();
+        for (name, value) in options.iter() {
+            match name {
+                "fuel" => parsed.fuel = Some(Self::bounded(&value, name, MAX_FUEL)?),
+                "fuel_per_call" => parsed.fuel_per_call = Self::flag(&value, name)?,
+                "interruptible" => parsed.interruptible = Self::flag(&value, name)?,
+                "timeout" => parsed.timeout = Some(Self::seconds(&value, name)?),
+                "max_memory_pages" => parsed.max_memory_pages = Some(Self::bounded(&value, name, 65536)? as u32),
+                "max_table_elements" => parsed.max_table_elements = Some(Self::bounded(&value, name, u32::MAX as u64)? as u32),
+                "stack_size" => parsed.stack_size = Some(Self::bounded(&value, name, usize::MAX as u64)? as usize),
+                "features" => parsed.features = Self::features(&value)?,
+                "target" => parsed.target = Some(Self::string(&value, name)?),
+                "cpu_features" => parsed.cpu_features = Self::strings(&value, name)?,
+                "" | "NA" => return Err("All runtime options must be named".to_string()),
+                other => return Err(format!("Unknown runtime option '{}'", other)),
+            }
+        }
+        if parsed.fuel_per_call && parsed.fuel.is_none() {
+            return Err("Option 'fuel_per_call' requires 'fuel'".to_string());
+        }
+        if parsed.timeout.is_some() && !parsed.interruptible {
+            return Err("Option 'timeout' requires 'interruptible'".to_string());
+        }
+        Ok(parsed)
+    }
+
+    /// The options that change the generated code, recorded with serialized artifacts
+    /// so that they are only loaded by runtimes configured the same way
+    pub fn artifact_config(&self) -> String {
+        let mut config = Vec::new();
+        if let Some(fuel) = self.fuel {
+            config.push(format!("fuel={}", fuel));
+        }
+        if let Some(pages) = self.max_memory_pages {
+            config.push(format!("max_memory_pages={}", pages));
+        }
+        if !self.interruptible {
+            config.push("interruptible=false".to_string());
+        }
+        for (name, enabled) in &self.features {
+            config.push(format!("{}={}", name, enabled));
+        }
+        config.join(",")
+    }
+
+    /// A non-negative whole number given as an integer or a double
+    pub fn count(value: &Robj, name: &str) -> std::result::Result<u64, String> {
+        let number = match (value.len(), value.as_integer(), value.as_real()) {
+            (1, Some(i), _) if !value.is_na() => i as f64,
+            (1, _, Some(x)) if x.is_finite() => x,
+            _ => return Err(format!("Option '{}' must be a single number", name)),
+        };
+        if number < 0.0 || number.fract() != 0.0 || number > u64::MAX as f64 {
+            return Err(format!("Option '{}' must be a non-negative whole number, got {}", name, number));
+        }
+        Ok(number as u64)
+    }
+
+    /// Named list of proposals to enable (`TRUE`) or disable (`FALSE`)
+    fn features(value: &Robj) -> std::result::Result<Vec<(String, bool)>, String> {
+        let list = value
+            .as_list()
+            .ok_or_else(|| "Option 'features' must be a named list of TRUE/FALSE values".to_string())?;
+        let mut features = Vec::with_capacity(list.len());
+        for (name, enabled) in list.iter() {
+            if name.is_empty() || name == "NA" {
+                return Err("All features must be named".to_string());
+            }
+            EngineFeatures::check_name(name)?;
+            features.push((name.to_string(), Self::flag(&enabled, name)?));
+        }
+        features.sort();
+        if let Some(pair) = features.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(format!("Feature '{}' is given more than once", pair[0].0));
+        }
+        Ok(features)
+    }
+
+    /// Character vector without missing values
+    pub fn strings(value: &Robj, name: &str) -> std::result::Result<Vec<String>, String> {
+        let strings = Strings::try_from(value.clone()).map_err(|_| format!("Option '{}' must be a character vector", name))?;
+        if strings.iter().any(|s| s.is_na()) {
+            return Err(format!("Option '{}' must not contain missing values", name));
+        }
+        Ok(strings.iter().map(|s| s.as_str().to_string()).collect())
+    }
+
+    pub fn string(value: &Robj, name: &str) -> std::result::Result<String, String> {
+        match Self::strings(value, name)?.as_slice() {
+            [string] => Ok(string.clone()),
+            _ => Err(format!("Option '{}' must be a single string", name)),
+        }
+    }
+
+    /// A count no larger than `max`
+    fn bounded(value: &Robj, name: &str, max: u64) -> std::result::Result<u64, String> {
+        let count = Self::count(value, name)?;
+        if count > max {
+            return Err(format!("Option '{}' must be at most {}, got {}", name, max, count));
+        }
+        Ok(count)
+    }
+
+    /// A positive duration given in seconds
+    pub fn seconds(value: &Robj, name: &str) -> std::result::Result<Duration, String> {
+        match (value.len(), value.as_integer(), value.as_real()) {
+            (1, Some(i), _) if !value.is_na() && i > 0 => Ok(Duration::from_secs(i as u64)),
+            (1, _, Some(x)) if x > 0.0 && x <= u32::MAX as f64 => Ok(Duration::from_secs_f64(x)),
+            _ => Err(format!("Option '{}' must be a positive number of seconds", name)),
+        }
+    }
+
+    pub fn flag(value: &Robj, name: &str) -> std::result::Result<bool, String> {
+        match (value.len(), value.as_bool()) {
+            (1, Some(flag)) if !value.is_na() => Ok(flag),
+            _ => Err(format!("Option '{}' must be TRUE or FALSE", name)),
+        }
+    }
+}
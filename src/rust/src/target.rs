@@ -0,0 +1,74 @@
+use std::str::FromStr;
+use wasmer_types::target::{CpuFeature, Target, Triple};
+
+/// Compilation targets: a triple and the CPU features the generated code may use
+pub struct EngineTarget;
+
+impl EngineTarget {
+    /// Target for a triple (the host when `None`) and CPU feature adjustments. Without adjustments
+    /// the host uses the features it detects and other triples their baseline; `"name"` adds a
+    /// feature and `"-name"` removes one.
+    pub fn resolve(triple: Option<&str>, cpu_features: &[String]) -> std::result::Result<Target, String> {
+        let host = Triple::host();
+        let triple = match triple {
+            Some(triple) => Triple::from_str(triple).map_err(|e| format!("Invalid target triple '{}': {}", triple, e))?,
+            None => host.clone(),
+        };
+        let mut features = if triple == host { CpuFeature::for_host() } else { Default::default() };
+        for entry in cpu_features {
+            let (name, enabled) = match entry.strip_prefix('-') {
+                Some(name) => (name, false),
+                None => (entry.as_str(), true),
+            };
+            let feature = CpuFeature::from_str(name).map_err(|_| format!("Unknown CPU feature '{}'", name))?;
+            if enabled {
+                features.insert(feature);
+            } else {
+                features.remove(feature);
+            }
+        }
+        Ok(Target::new(triple, features))
+    }
+
+    /// `<triple>+<feature>,<feature>...`, as recorded in serialized artifacts and cache keys
+    pub fn describe(target: &Target) -> String {
+        let mut features: Vec<String> = target.cpu_features().iter().map(|f| f.to_string()).collect();
+        features.sort();
+        format!("{}+{}", target.triple(), features.join(","))
+    }
+
+    /// Whether code generated for `target` can run on this machine
+    pub fn is_host_compatible(target: &Target) -> bool {
+        *target.triple() == Triple::host() && CpuFeature::for_host().is_superset(*target.cpu_features())
+    }
+
+    /// Check that an artifact compiled for `artifact` (as given by [`EngineTarget::describe`]) can be
+    /// loaded by a runtime targeting `runtime`: same triple, and no CPU feature the runtime lacks
+    pub fn check_artifact(artifact: &str, runtime: &str) -> std::result::Result<(), String> {
+        let split = |target: &'_ str| -> (String, Vec<String>) {
+            let (triple, features) = target.split_once('+').unwrap_or((target, ""));
+            let features = features.split(',').filter(|f| !f.is_empty()).map(str::to_string).collect();
+            (triple.to_string(), features)
+        };
+        let (artifact_triple, artifact_features) = split(artifact);
+        let (runtime_triple, runtime_features) = split(runtime);
+        if artifact_triple != runtime_triple {
+            return Err(format!(
+                "Artifact was compiled for target '{}' but the runtime targets '{}'",
+                artifact_triple, runtime_triple
+            ));
+        }
+        let missing: Vec<&str> = artifact_features
+            .iter()
+            .filter(|f| !runtime_features.contains(f))
+            .map(|f| f.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!(
+                "Artifact uses CPU features the runtime's target lacks: {}",
+                missing.join(", ")
+            ));
+        }
+        Ok(())
+    }
+}
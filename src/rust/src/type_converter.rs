@@ -1,9 +1,122 @@
 use extendr_api::prelude::*;
-use wasmer::{Value, Type};
+use extendr_api::wrapper::ExternalPtr;
+use std::cell::Cell;
+use wasmer::{Function, Value, Type};
 
 /// Utilities for converting between R and WebAssembly types
 pub struct TypeConverter;
 
+/// How 64-bit integers are returned to R, set with `options(wasmer.int64 = ...)`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Int64Mode {
+    /// Doubles when exactly representable (|x| <= 2^53), decimal strings otherwise (`"auto"`)
+    Auto,
+    /// Always doubles, possibly losing precision (`"double"`)
+    Double,
+    /// `bit64::integer64` vectors (`"integer64"`)
+    Integer64,
+    /// Always decimal strings (`"character"`, the default)
+    Character,
+}
+
+/// A column of wasm numbers of one type, converted from an R vector
+#[derive(Debug, Clone)]
+pub enum WasmColumn {
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+}
+
+impl WasmColumn {
+    pub fn len(&self) -> usize {
+        match self {
+            WasmColumn::I32(v) => v.len(),
+            WasmColumn::I64(v) => v.len(),
+            WasmColumn::F32(v) => v.len(),
+            WasmColumn::F64(v) => v.len(),
+        }
+    }
+
+    /// Value at row `i`; columns of length one are recycled
+    pub fn value(&self, i: usize) -> Value {
+        let i = if self.len() == 1 { 0 } else { i };
+        match self {
+            WasmColumn::I32(v) => Value::I32(v[i]),
+            WasmColumn::I64(v) => Value::I64(v[i]),
+            WasmColumn::F32(v) => Value::F32(v[i]),
+            WasmColumn::F64(v) => Value::F64(v[i]),
+        }
+    }
+
+    /// An empty column for results of the given type
+    pub fn with_capacity(ty: Type, capacity: usize) -> std::result::Result<WasmColumn, String> {
+        match ty {
+            Type::I32 => Ok(WasmColumn::I32(Vec::with_capacity(capacity))),
+            Type::I64 => Ok(WasmColumn::I64(Vec::with_capacity(capacity))),
+            Type::F32 => Ok(WasmColumn::F32(Vec::with_capacity(capacity))),
+            Type::F64 => Ok(WasmColumn::F64(Vec::with_capacity(capacity))),
+            _ => Err(format!("{} results cannot be returned as vectors", TypeConverter::type_name(ty))),
+        }
+    }
+
+    /// Append a value of the column's type
+    pub fn push(&mut self, value: &Value) {
+        match (self, value) {
+            (WasmColumn::I32(v), Value::I32(x)) => v.push(*x),
+            (WasmColumn::I64(v), Value::I64(x)) => v.push(*x),
+            (WasmColumn::F32(v), Value::F32(x)) => v.push(*x),
+            (WasmColumn::F64(v), Value::F64(x)) => v.push(*x),
+            _ => unreachable!("result values always match the result type of their column"),
+        }
+    }
+
+    /// Convert to an R vector; `i64` columns follow the `wasmer.int64` option
+    pub fn into_r(self, int64_mode: Int64Mode) -> Robj {
+        match self {
+            WasmColumn::I32(v) => r!(v),
+            WasmColumn::I64(v) => TypeConverter::i64_vector_to_r(&v, int64_mode),
+            WasmColumn::F32(v) => r!(v.into_iter().map(|x| x as f64).collect::<Vec<f64>>()),
+            WasmColumn::F64(v) => r!(v),
+        }
+    }
+}
+
+/// Largest magnitude up to which every integer is exactly representable as a double
+const MAX_SAFE_INTEGER: i64 = 1 << 53;
+
+thread_local! {
+    /// Mode of the wasm call in progress, used by the host functions it calls back
+    static CALL_INT64_MODE: Cell<Option<Int64Mode>> = const { Cell::new(None) };
+}
+
+impl Int64Mode {
+    /// Read the `wasmer.int64` option
+    pub fn from_option() -> Int64Mode {
+        let option = eval_string("getOption('wasmer.int64', 'character')").ok();
+        match option.as_ref().and_then(|o| o.as_str()) {
+            Some("auto") => Int64Mode::Auto,
+            Some("double") => Int64Mode::Double,
+            Some("integer64") => Int64Mode::Integer64,
+            _ => Int64Mode::Character,
+        }
+    }
+
+    /// The mode of the wasm call in progress, or the option outside of calls
+    pub fn current() -> Int64Mode {
+        CALL_INT64_MODE.with(Cell::get).unwrap_or_else(Int64Mode::from_option)
+    }
+
+    /// Run a wasm call with the option read once, for its results and any host functions it calls
+    pub fn during<T>(call: impl FnOnce(Int64Mode) -> T) -> T {
+        let mode = Int64Mode::current();
+        let outer = CALL_INT64_MODE.with(|current| current.replace(Some(mode)));
+        let result = call(mode);
+        CALL_INT64_MODE.with(|current| current.set(outer));
+        result
+    }
+}
+
 impl TypeConverter {
     /// Convert R value to WebAssembly Value
     pub fn r_to_wasm(r_value: &Robj) -> std::result::Result<Value, String> {
@@ -33,11 +146,249 @@ impl TypeConverter {
         }
     }
 
+    /// Parse a WebAssembly value type name ("i32", "i64", "f32", "f64", "funcref", "externref")
+    pub fn parse_type(type_name: &str) -> std::result::Result<Type, String> {
+        match type_name.to_lowercase().as_str() {
+            "i32" => Ok(Type::I32),
+            "i64" => Ok(Type::I64),
+            "f32" => Ok(Type::F32),
+            "f64" => Ok(Type::F64),
+            "funcref" => Ok(Type::FuncRef),
+            "externref" => Ok(Type::ExternRef),
+            _ => Err(format!(
+                "Unknown value type: '{}'. Available types: i32, i64, f32, f64, funcref, externref",
+                type_name
+            )),
+        }
+    }
+
+    /// Convert R value to a WebAssembly Value of the requested type
+    pub fn r_to_wasm_typed(r_value: &Robj, ty: Type) -> std::result::Result<Value, String> {
+        match ty {
+            Type::FuncRef => {
+                if r_value.is_null() {
+                    Ok(Value::FuncRef(None))
+                } else {
+                    ExternalPtr::<Function>::try_from(r_value.clone())
+                        .map(|f| Value::FuncRef(Some(f.as_ref().clone())))
+                        .map_err(|_| "funcref values must be NULL or a Function pointer".to_string())
+                }
+            }
+            Type::ExternRef => {
+                if r_value.is_null() {
+                    Ok(Value::ExternRef(None))
+                } else {
+                    Err("Only NULL externref values can be created from R".to_string())
+                }
+            }
+            Type::V128 | Type::ExceptionRef => Err(format!("{} values are not supported", Self::type_name(ty))),
+            Type::I32 | Type::I64 | Type::F32 | Type::F64 => {
+                if r_value.len() != 1 {
+                    return Err(format!(
+                        "expected a single value for {}, got length {}",
+                        Self::type_name(ty),
+                        r_value.len()
+                    ));
+                }
+                Ok(Self::r_to_column(r_value, ty)?.value(0))
+            }
+        }
+    }
+
+    /// Name of a value type as written in WAT
+    pub fn type_name(ty: Type) -> &'static str {
+        match ty {
+            Type::I32 => "i32",
+            Type::I64 => "i64",
+            Type::F32 => "f32",
+            Type::F64 => "f64",
+            Type::V128 => "v128",
+            Type::FuncRef => "funcref",
+            Type::ExternRef => "externref",
+            Type::ExceptionRef => "exnref",
+        }
+    }
+
+    /// Convert an R vector to a column of wasm numbers of the given type, validating every element.
+    ///
+    /// Integer types need whole numbers in range; `i32` also accepts unsigned values up to
+    /// 2^32 - 1, which keep their bit pattern. `i64` additionally accepts `bit64::integer64` and
+    /// decimal or `0x` hex strings, which may hold unsigned values up to 2^64 - 1; doubles beyond
+    /// 2^53 are rejected for `i64` because they may not be exact. NA is rejected for every type.
+    pub fn r_to_column(r_value: &Robj, ty: Type) -> std::result::Result<WasmColumn, String> {
+        let single = r_value.len() == 1;
+        let at = |i: usize, e: String| if single { e } else { format!("element {}: {}", i + 1, e) };
+        match ty {
+            Type::I64 if r_value.inherits("integer64") => {
+                let bits = r_value.as_real_slice().ok_or_else(|| "Could not read integer64 values".to_string())?;
+                bits.iter()
+                    .enumerate()
+                    .map(|(i, b)| match b.to_bits() as i64 {
+                        // bit64 uses the smallest i64 as NA
+                        i64::MIN => Err(at(i, "NA cannot be passed as i64".to_string())),
+                        v => Ok(v),
+                    })
+                    .collect::<std::result::Result<Vec<i64>, String>>()
+                    .map(WasmColumn::I64)
+            }
+            Type::I64 if r_value.rtype() == Rtype::Strings => r_value
+                .as_str_iter()
+                .ok_or_else(|| "Could not read strings as i64".to_string())?
+                .enumerate()
+                .map(|(i, text)| {
+                    if text.is_na() {
+                        Err(at(i, "NA cannot be passed as i64".to_string()))
+                    } else {
+                        Self::parse_i64(text).map_err(|e| at(i, e))
+                    }
+                })
+                .collect::<std::result::Result<Vec<i64>, String>>()
+                .map(WasmColumn::I64),
+            Type::I32 | Type::I64 | Type::F32 | Type::F64 => {
+                let numbers = Self::numeric_elements(r_value, ty)?;
+                fn convert<T>(
+                    numbers: &[f64],
+                    f: fn(f64) -> std::result::Result<T, String>,
+                    at: impl Fn(usize, String) -> String,
+                ) -> std::result::Result<Vec<T>, String> {
+                    numbers.iter().enumerate().map(|(i, v)| f(*v).map_err(|e| at(i, e))).collect()
+                }
+                Ok(match ty {
+                    Type::I32 => WasmColumn::I32(convert(&numbers, Self::double_to_i32, at)?),
+                    Type::I64 => WasmColumn::I64(convert(&numbers, Self::double_to_i64, at)?),
+                    Type::F32 => WasmColumn::F32(convert(&numbers, Self::double_to_f32, at)?),
+                    _ => WasmColumn::F64(numbers),
+                })
+            }
+            _ => Err(format!("{} values cannot be passed as vectors", Self::type_name(ty))),
+        }
+    }
+
+    /// Read the elements of an integer, double or logical vector as doubles, rejecting NA
+    fn numeric_elements(r_value: &Robj, ty: Type) -> std::result::Result<Vec<f64>, String> {
+        let na_error = |i: usize| {
+            let message = format!("NA cannot be passed as {}", Self::type_name(ty));
+            if r_value.len() == 1 { message } else { format!("element {}: {}", i + 1, message) }
+        };
+        match r_value.rtype() {
+            Rtype::Integers => r_value
+                .as_integer_slice()
+                .unwrap_or(&[])
+                .iter()
+                .enumerate()
+                .map(|(i, v)| if *v == i32::MIN { Err(na_error(i)) } else { Ok(*v as f64) })
+                .collect(),
+            Rtype::Doubles => r_value
+                .as_real_slice()
+                .unwrap_or(&[])
+                .iter()
+                .enumerate()
+                .map(|(i, v)| if Rfloat::from(*v).is_na() { Err(na_error(i)) } else { Ok(*v) })
+                .collect(),
+            Rtype::Logicals => r_value
+                .as_logical_slice()
+                .unwrap_or(&[])
+                .iter()
+                .enumerate()
+                .map(|(i, v)| if v.is_na() { Err(na_error(i)) } else { Ok(if v.is_true() { 1.0 } else { 0.0 }) })
+                .collect(),
+            _ => Err(format!("expected a number for {}, got {:?}", Self::type_name(ty), r_value.rtype())),
+        }
+    }
+
+    fn double_to_i32(v: f64) -> std::result::Result<i32, String> {
+        if v.fract() != 0.0 {
+            return Err(format!("{} is not a whole number and cannot be passed as i32", v));
+        }
+        // Values up to 2^32 - 1 are accepted as unsigned and keep their bit pattern
+        if v < i32::MIN as f64 || v > u32::MAX as f64 {
+            return Err(format!("{} is out of range for i32", v));
+        }
+        Ok(v as i64 as i32)
+    }
+
+    fn double_to_i64(v: f64) -> std::result::Result<i64, String> {
+        if v.fract() != 0.0 || !v.is_finite() {
+            return Err(format!("{} is not a whole number and cannot be passed as i64", v));
+        }
+        if v.abs() > MAX_SAFE_INTEGER as f64 {
+            return Err(format!(
+                "{} is beyond 2^53 and may not be exact as a double; pass a bit64::integer64 or a string",
+                v
+            ));
+        }
+        Ok(v as i64)
+    }
+
+    fn double_to_f32(v: f64) -> std::result::Result<f32, String> {
+        if v.is_finite() && v.abs() > f32::MAX as f64 {
+            return Err(format!("{} is out of range for f32", v));
+        }
+        Ok(v as f32)
+    }
+
+    /// Parse a decimal or `0x`-prefixed hexadecimal 64-bit integer
+    pub fn parse_i64(text: &str) -> std::result::Result<i64, String> {
+        let trimmed = text.trim();
+        let (negative, digits) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+        };
+        let magnitude = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => digits.parse::<u64>(),
+        }
+        .map_err(|_| format!("'{}' is not a valid 64-bit integer", text))?;
+        if negative {
+            if magnitude > i64::MAX as u64 + 1 {
+                return Err(format!("'{}' is out of range for i64", text));
+            }
+            Ok((magnitude as i64).wrapping_neg())
+        } else {
+            Ok(magnitude as i64)
+        }
+    }
+
+    /// Convert a 64-bit integer to R according to the `wasmer.int64` option
+    pub fn i64_to_r(value: i64, mode: Int64Mode) -> Robj {
+        Self::i64_vector_to_r(&[value], mode)
+    }
+
+    /// Convert 64-bit integers to an R vector according to the `wasmer.int64` option.
+    /// In `"auto"` mode the whole vector becomes character if any value is not exactly representable.
+    pub fn i64_vector_to_r(values: &[i64], mode: Int64Mode) -> Robj {
+        let lossless = values.iter().all(|v| (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(v));
+        match mode {
+            Int64Mode::Integer64 => {
+                let bits: Vec<f64> = values.iter().map(|v| f64::from_bits(*v as u64)).collect();
+                let mut robj = r!(bits);
+                robj.set_class(&["integer64"]).unwrap();
+                robj
+            }
+            Int64Mode::Character => r!(values.iter().map(|v| v.to_string()).collect::<Vec<String>>()),
+            Int64Mode::Auto if !lossless => r!(values.iter().map(|v| v.to_string()).collect::<Vec<String>>()),
+            _ => r!(values.iter().map(|v| *v as f64).collect::<Vec<f64>>()),
+        }
+    }
+
     /// Convert WebAssembly Value to R value
     pub fn wasm_to_r(wasm_value: &Value) -> Robj {
+        Self::wasm_to_r_with(wasm_value, Int64Mode::current())
+    }
+
+    /// Convert the value of a global; reference-typed globals have no R representation
+    pub fn global_to_r(wasm_value: &Value) -> std::result::Result<Robj, String> {
+        match wasm_value {
+            Value::I32(_) | Value::I64(_) | Value::F32(_) | Value::F64(_) => Ok(Self::wasm_to_r(wasm_value)),
+            other => Err(format!("{} globals cannot be read from R", Self::type_name(other.ty()))),
+        }
+    }
+
+    /// Convert WebAssembly Value to R value using the given representation for 64-bit integers
+    pub fn wasm_to_r_with(wasm_value: &Value, int64_mode: Int64Mode) -> Robj {
         match wasm_value {
             Value::I32(i) => r!(*i),
-            Value::I64(i) => r!(*i as f64), // R doesn't have i64, use f64
+            Value::I64(i) => Self::i64_to_r(*i, int64_mode),
             Value::F32(f) => r!(*f as f64),
             Value::F64(f) => r!(*f),
             Value::V128(_) => r!(NA_REAL), // Not supported
@@ -47,7 +398,33 @@ impl TypeConverter {
         }
     }
 
+    /// Split an R list or atomic vector into its elements, keeping the `integer64` class
+    pub fn split_elements(r_value: &Robj) -> Vec<Robj> {
+        if let Some(list) = r_value.as_list() {
+            return list.iter().map(|(_, v)| v).collect();
+        }
+        if r_value.inherits("integer64") {
+            return r_value
+                .as_real_slice()
+                .unwrap_or(&[])
+                .iter()
+                .map(|bits| {
+                    let mut element = r!(*bits);
+                    element.set_class(&["integer64"]).unwrap();
+                    element
+                })
+                .collect();
+        }
+        match r_value.rtype() {
+            Rtype::Integers => r_value.as_integer_slice().unwrap_or(&[]).iter().map(|v| r!(*v)).collect(),
+            Rtype::Doubles => r_value.as_real_slice().unwrap_or(&[]).iter().map(|v| r!(*v)).collect(),
+            Rtype::Strings => r_value.as_str_vector().unwrap_or_default().into_iter().map(|v| r!(v)).collect(),
+            _ => vec![r_value.clone()],
+        }
+    }
+
     /// Convert R vector to WebAssembly Value vector
+    #[allow(dead_code)]
     pub fn r_vector_to_wasm(r_values: List) -> std::result::Result<Vec<Value>, String> {
         let mut wasm_values = Vec::new();
         
@@ -62,9 +439,9 @@ impl TypeConverter {
     }
 
     /// Convert WebAssembly Value vector to R list
-    pub fn wasm_vector_to_r(wasm_values: &[Value]) -> Robj {
+    pub fn wasm_vector_to_r(wasm_values: &[Value], int64_mode: Int64Mode) -> Robj {
         let r_values: Vec<Robj> = wasm_values.iter()
-            .map(|v| Self::wasm_to_r(v))
+            .map(|v| Self::wasm_to_r_with(v, int64_mode))
             .collect();
         
         // Convert to a simple vector if single value, otherwise return a list
@@ -76,20 +453,18 @@ impl TypeConverter {
         }
     }
 
-    /// Get WebAssembly type from R value
-    #[allow(dead_code)]
-    pub fn r_to_wasm_type(r_value: &Robj) -> std::result::Result<Type, String> {
-        match r_value.rtype() {
-            Rtype::Integers => Ok(Type::I32),
-            Rtype::Doubles => Ok(Type::F64),
-            Rtype::Logicals => Ok(Type::I32),
-            _ => Err(format!("Unsupported R type for WASM: {:?}", r_value.rtype())),
-        }
+    /// Build a data frame from equal-length column vectors
+    pub fn data_frame(names: &[&str], columns: Vec<Robj>) -> Robj {
+        let nrow = columns.first().map_or(0, |col| col.len()) as i32;
+        let mut df: Robj = List::from_names_and_values(names, columns).unwrap().into();
+        df.set_attrib("row.names", (1..=nrow).collect::<Vec<i32>>()).unwrap();
+        df.set_class(&["data.frame"]).unwrap();
+        df
     }
 
-    /// Validate that R arguments match expected WebAssembly function signature
-    #[allow(dead_code)]
-    pub fn validate_args(r_args: &List, expected_types: &[Type]) -> std::result::Result<(), String> {
+    /// Validate R arguments against a function's parameter types and convert them.
+    /// Fails on an arity mismatch before any conversion is attempted.
+    pub fn validate_args(r_args: &List, expected_types: &[Type]) -> std::result::Result<Vec<Value>, String> {
         if r_args.len() != expected_types.len() {
             return Err(format!(
                 "Argument count mismatch: expected {}, got {}",
@@ -98,16 +473,14 @@ impl TypeConverter {
             ));
         }
 
+        let mut wasm_values = Vec::with_capacity(expected_types.len());
         for (i, (_name, arg)) in r_args.iter().enumerate() {
-            let r_type = Self::r_to_wasm_type(&arg)?;
-            if r_type != expected_types[i] {
-                return Err(format!(
-                    "Argument type mismatch at position {}: expected {:?}, got {:?}",
-                    i, expected_types[i], r_type
-                ));
-            }
+            let value = Self::r_to_wasm_typed(&arg, expected_types[i]).map_err(|e| {
+                format!("Argument {} ({}): {}", i + 1, Self::type_name(expected_types[i]), e)
+            })?;
+            wasm_values.push(value);
         }
 
-        Ok(())
+        Ok(wasm_values)
     }
 }
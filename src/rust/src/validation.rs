@@ -0,0 +1,73 @@
+use extendr_api::prelude::*;
+use wasmer::wasmparser::{BinaryReaderError, FuncValidatorAllocations, Parser, ValidPayload, Validator};
+use wasmer_types::Features;
+use crate::features::EngineFeatures;
+use crate::type_converter::TypeConverter;
+
+/// A problem found while validating a module
+pub struct ValidationError {
+    /// Byte offset in the binary, unknown for WAT syntax errors
+    pub offset: Option<usize>,
+    /// Index of the function whose body is invalid, if the problem is in a function body
+    pub function: Option<u32>,
+    pub message: String,
+}
+
+impl ValidationError {
+    fn binary(error: BinaryReaderError, function: Option<u32>) -> Self {
+        Self { offset: Some(error.offset()), function, message: error.message().to_string() }
+    }
+}
+
+/// Validation of wasm binaries without compiling them
+pub struct ModuleValidator;
+
+impl ModuleValidator {
+    /// Validate `bytes` with the given proposals enabled. Function bodies are checked independently,
+    /// so every invalid body is reported; an error outside function bodies ends the validation.
+    pub fn validate(bytes: &[u8], features: &Features) -> Vec<ValidationError> {
+        let mut validator = Validator::new_with_features(EngineFeatures::for_validator(features));
+        let mut allocations = FuncValidatorAllocations::default();
+        let mut errors = Vec::new();
+        for payload in Parser::new(0).parse_all(bytes) {
+            let payload = match payload {
+                Ok(payload) => payload,
+                Err(e) => {
+                    errors.push(ValidationError::binary(e, None));
+                    break;
+                }
+            };
+            match validator.payload(&payload) {
+                Ok(ValidPayload::Func(func, body)) => {
+                    let index = func.index;
+                    let mut func_validator = func.into_validator(std::mem::take(&mut allocations));
+                    if let Err(e) = func_validator.validate(&body) {
+                        errors.push(ValidationError::binary(e, Some(index)));
+                    }
+                    allocations = func_validator.into_allocations();
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    errors.push(ValidationError::binary(e, None));
+                    break;
+                }
+            }
+        }
+        errors
+    }
+
+    /// `list(valid, errors)` with the errors as a data frame of `offset`, `function` and `message`
+    pub fn report(errors: &[ValidationError]) -> Robj {
+        let optional = |value: Option<f64>| value.map_or(Rfloat::na(), Rfloat::from);
+        let offsets = Doubles::from_values(errors.iter().map(|e| optional(e.offset.map(|o| o as f64))));
+        let functions = Doubles::from_values(errors.iter().map(|e| optional(e.function.map(f64::from))));
+        let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+        let frame = TypeConverter::data_frame(
+            &["offset", "function", "message"],
+            vec![offsets.into(), functions.into(), r!(messages)],
+        );
+        List::from_names_and_values(["valid", "errors"], [r!(errors.is_empty()), frame])
+            .unwrap()
+            .into()
+    }
+}
@@ -0,0 +1,178 @@
+use extendr_api::prelude::*;
+use wasmer::{Function, RuntimeError, Store, Type};
+use crate::error::{WasmerError, WasmerResult};
+use crate::type_converter::{Int64Mode, TypeConverter, WasmColumn};
+
+/// Element-wise calls of a wasm function over parallel R vectors
+pub struct VectorizedCall;
+
+impl VectorizedCall {
+    /// Call `func` once per row of `args`, a list (or data frame) with one vector per parameter.
+    /// Length-one vectors are recycled. Returns a vector for single-result functions and a
+    /// data frame with one column per result otherwise.
+    pub fn call(
+        store: &mut Store,
+        func: &Function,
+        args: &List,
+        instance_name: &str,
+        function_name: &str,
+        int64_mode: Int64Mode,
+    ) -> WasmerResult<Robj> {
+        let argument_error = |message: String| WasmerError::Argument {
+            function: Some(function_name.to_string()),
+            message,
+        };
+        let func_ty = func.ty(store);
+        let param_types = func_ty.params().to_vec();
+        let result_types = func_ty.results().to_vec();
+        if args.len() != param_types.len() {
+            return Err(argument_error(format!(
+                "Argument count mismatch: expected {}, got {}",
+                param_types.len(),
+                args.len()
+            )));
+        }
+
+        let mut columns = Vec::with_capacity(param_types.len());
+        for (i, (_name, arg)) in args.iter().enumerate() {
+            let column = TypeConverter::r_to_column(&arg, param_types[i]).map_err(|e| {
+                argument_error(format!("Argument {} ({}): {}", i + 1, TypeConverter::type_name(param_types[i]), e))
+            })?;
+            columns.push(column);
+        }
+        let nrow = Self::row_count(&columns).map_err(argument_error)?;
+
+        let results = match Self::typed_call(store, func, &columns, &result_types, nrow) {
+            Some(results) => results,
+            None => Self::dynamic_call(store, func, &columns, &result_types, nrow),
+        }
+        .map_err(|e| match e {
+            RowError::Trap(row, error) => match WasmerError::trap(instance_name, function_name, error) {
+                WasmerError::Trap { instance, function, message, trap } => WasmerError::Trap {
+                    instance,
+                    function,
+                    message: format!("{} (row {})", message, row + 1),
+                    trap,
+                },
+                other => other,
+            },
+            RowError::Unsupported(message) => argument_error(message),
+        })?;
+
+        match results.len() {
+            0 => Ok(r!(NULL)),
+            1 => Ok(results.into_iter().next().unwrap().into_r(int64_mode)),
+            n => {
+                let names: Vec<String> = (1..=n).map(|i| format!("result{}", i)).collect();
+                let names: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+                let values = results.into_iter().map(|column| column.into_r(int64_mode)).collect();
+                Ok(TypeConverter::data_frame(&names, values))
+            }
+        }
+    }
+
+    /// Number of rows: all vectors must have the same length, except length-one vectors
+    fn row_count(columns: &[WasmColumn]) -> std::result::Result<usize, String> {
+        let mut nrow: Option<usize> = None;
+        for (i, column) in columns.iter().enumerate() {
+            if column.len() == 1 {
+                continue;
+            }
+            match nrow {
+                None => nrow = Some(column.len()),
+                Some(n) if n != column.len() => {
+                    return Err(format!(
+                        "Argument {} has length {}, expected {} or 1",
+                        i + 1,
+                        column.len(),
+                        n
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(nrow.unwrap_or(1))
+    }
+
+    /// Loop through a `TypedFunction` for the most common signatures; `None` for the others
+    fn typed_call(
+        store: &mut Store,
+        func: &Function,
+        columns: &[WasmColumn],
+        result_types: &[Type],
+        nrow: usize,
+    ) -> Option<std::result::Result<Vec<WasmColumn>, RowError>> {
+        match (columns, result_types) {
+            ([WasmColumn::I32(a)], [Type::I32]) => {
+                let typed = func.typed::<i32, i32>(store).ok()?;
+                Some(collect_rows(nrow, |i| typed.call(store, at(a, i))).map(|v| vec![WasmColumn::I32(v)]))
+            }
+            ([WasmColumn::I32(a), WasmColumn::I32(b)], [Type::I32]) => {
+                let typed = func.typed::<(i32, i32), i32>(store).ok()?;
+                Some(collect_rows(nrow, |i| typed.call(store, at(a, i), at(b, i))).map(|v| vec![WasmColumn::I32(v)]))
+            }
+            ([WasmColumn::I64(a)], [Type::I64]) => {
+                let typed = func.typed::<i64, i64>(store).ok()?;
+                Some(collect_rows(nrow, |i| typed.call(store, at(a, i))).map(|v| vec![WasmColumn::I64(v)]))
+            }
+            ([WasmColumn::F64(a)], [Type::F64]) => {
+                let typed = func.typed::<f64, f64>(store).ok()?;
+                Some(collect_rows(nrow, |i| typed.call(store, at(a, i))).map(|v| vec![WasmColumn::F64(v)]))
+            }
+            ([WasmColumn::F64(a), WasmColumn::F64(b)], [Type::F64]) => {
+                let typed = func.typed::<(f64, f64), f64>(store).ok()?;
+                Some(collect_rows(nrow, |i| typed.call(store, at(a, i), at(b, i))).map(|v| vec![WasmColumn::F64(v)]))
+            }
+            _ => None,
+        }
+    }
+
+    /// Loop through `Function::call` for any other signature, reusing the argument buffer
+    fn dynamic_call(
+        store: &mut Store,
+        func: &Function,
+        columns: &[WasmColumn],
+        result_types: &[Type],
+        nrow: usize,
+    ) -> std::result::Result<Vec<WasmColumn>, RowError> {
+        let mut results = result_types
+            .iter()
+            .map(|ty| WasmColumn::with_capacity(*ty, nrow))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(RowError::Unsupported)?;
+        let mut row = Vec::with_capacity(columns.len());
+        for i in 0..nrow {
+            row.clear();
+            row.extend(columns.iter().map(|column| column.value(i)));
+            let values = func.call(store, &row).map_err(|e| RowError::Trap(i, e))?;
+            for (column, value) in results.iter_mut().zip(values.iter()) {
+                column.push(value);
+            }
+        }
+        Ok(results)
+    }
+}
+
+/// Failure of a vectorised call
+enum RowError {
+    /// The call trapped at the given row
+    Trap(usize, RuntimeError),
+    /// The function returns a type that cannot be collected into an R vector
+    Unsupported(String),
+}
+
+/// Element `i` of a column, recycling length-one columns
+fn at<T: Copy>(values: &[T], i: usize) -> T {
+    if values.len() == 1 { values[0] } else { values[i] }
+}
+
+fn collect_rows<T>(
+    nrow: usize,
+    mut call: impl FnMut(usize) -> std::result::Result<T, RuntimeError>,
+) -> std::result::Result<Vec<T>, RowError> {
+    let mut out = Vec::with_capacity(nrow);
+    for i in 0..nrow {
+        out.push(call(i).map_err(|e| RowError::Trap(i, e))?);
+    }
+    Ok(out)
+}
@@ -0,0 +1,108 @@
+use std::path::{Component, Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::runtime::Handle;
+use wasmer_wasix::virtual_fs::{mem_fs, FileSystem};
+
+/// A host directory made visible to a WASI program
+#[derive(Debug, Clone)]
+pub struct Preopen {
+    /// Path the program sees
+    pub guest: String,
+    pub host: String,
+    pub writable: bool,
+}
+
+/// The in-memory filesystem of WASI programs that do not see the host's.
+///
+/// Files are read and written through the async `VirtualFile` interface; memory files are always
+/// ready, so blocking on the tokio runtime the WASI environments use returns at once.
+pub struct WasiFs;
+
+impl WasiFs {
+    /// A filesystem holding `files`, keyed by their path from the root
+    pub fn memory(files: &[(String, Vec<u8>)]) -> std::result::Result<mem_fs::FileSystem, String> {
+        let fs = mem_fs::FileSystem::default();
+        for (path, contents) in files {
+            let path = Self::guest_path(path)?;
+            let mut dir = PathBuf::from("/");
+            for component in path.parent().into_iter().flat_map(|parent| parent.components().skip(1)) {
+                dir.push(component);
+                if fs.read_dir(&dir).is_err() {
+                    fs.create_dir(&dir)
+                        .map_err(|e| format!("Cannot create directory '{}': {}", dir.display(), e))?;
+                }
+            }
+            let mut file = fs
+                .new_open_options()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&path)
+                .map_err(|e| format!("Cannot create '{}': {}", path.display(), e))?;
+            Handle::current()
+                .block_on(file.write_all(contents))
+                .map_err(|e| format!("Cannot write '{}': {}", path.display(), e))?;
+        }
+        Ok(fs)
+    }
+
+    /// Every file of `fs` with its contents, sorted by path
+    pub fn read_all(fs: &mem_fs::FileSystem) -> std::result::Result<Vec<(String, Vec<u8>)>, String> {
+        let mut files = Vec::new();
+        let mut dirs = vec![PathBuf::from("/")];
+        while let Some(dir) = dirs.pop() {
+            let entries = fs
+                .read_dir(&dir)
+                .map_err(|e| format!("Cannot list '{}': {}", dir.display(), e))?;
+            for entry in entries.flatten() {
+                if entry.file_type().map(|ty| ty.is_dir()).unwrap_or(false) {
+                    dirs.push(entry.path);
+                    continue;
+                }
+                let mut file = fs
+                    .new_open_options()
+                    .read(true)
+                    .open(&entry.path)
+                    .map_err(|e| format!("Cannot open '{}': {}", entry.path.display(), e))?;
+                let mut contents = Vec::new();
+                Handle::current()
+                    .block_on(file.read_to_end(&mut contents))
+                    .map_err(|e| format!("Cannot read '{}': {}", entry.path.display(), e))?;
+                files.push((entry.path.to_string_lossy().into_owned(), contents));
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Check the paths of the files of an in-memory filesystem
+    pub fn check_files(files: &[(String, Vec<u8>)]) -> std::result::Result<(), String> {
+        files.iter().try_for_each(|(path, _)| Self::guest_path(path).map(|_| ()))
+    }
+
+    /// Check that a host directory can be preopened
+    pub fn check_preopen(preopen: &Preopen) -> std::result::Result<(), String> {
+        Self::guest_path(&preopen.guest)?;
+        if !Path::new(&preopen.host).is_dir() {
+            return Err(format!("Cannot preopen '{}': not a directory", preopen.host));
+        }
+        Ok(())
+    }
+
+    /// Absolute guest path for `path`, which may be given relative to the root but must not
+    /// leave it
+    fn guest_path(path: &str) -> std::result::Result<PathBuf, String> {
+        let mut guest = PathBuf::from("/");
+        for component in Path::new(path).components() {
+            match component {
+                Component::Normal(name) => guest.push(name),
+                Component::RootDir | Component::CurDir => {}
+                _ => return Err(format!("Invalid guest path '{}'", path)),
+            }
+        }
+        if guest == Path::new("/") && !path.starts_with('/') {
+            return Err(format!("Invalid guest path '{}'", path));
+        }
+        Ok(guest)
+    }
+}
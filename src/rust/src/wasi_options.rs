@@ -0,0 +1,174 @@
+use extendr_api::prelude::*;
+use crate::runtime_options::RuntimeOptions;
+use crate::wasi_fs::Preopen;
+use crate::wasi_output::OutputMode;
+
+/// Options of `wasmer_wasi_state_new_ext()`, given in R as a named list
+#[derive(Debug, Clone)]
+pub struct WasiOptions {
+    /// Program arguments following the program name
+    pub args: Vec<String>,
+    /// Environment variables, in the order given
+    pub env: Vec<(String, String)>,
+    pub stdin: StdinSource,
+    pub stdout: OutputMode,
+    pub stderr: OutputMode,
+    /// Host directories the program sees
+    pub preopens: Vec<Preopen>,
+    /// Files of an in-memory filesystem replacing the host's, when one is requested
+    pub memory_files: Option<Vec<(String, Vec<u8>)>>,
+    /// Most threads a WASIX program may run at once, unlimited if `None`
+    pub max_threads: Option<usize>,
+}
+
+/// What a WASI program reads from stdin
+#[derive(Debug, Clone)]
+pub enum StdinSource {
+    Bytes(Vec<u8>),
+    /// Host file read when the WASI state is created
+    File(String),
+}
+
+impl Default for WasiOptions {
+    fn default() -> Self {
+        Self {
+            args: Vec::new(),
+            env: Vec::new(),
+            stdin: StdinSource::Bytes(Vec::new()),
+            stdout: OutputMode::Capture,
+            stderr: OutputMode::Capture,
+            preopens: Vec::new(),
+            memory_files: None,
+            max_threads: None,
+        }
+    }
+}
+
+impl WasiOptions {
+    pub fn from_list(options: &List) -> std::result::Result<Self, String> {
+        let mut parsed = WasiOptions::default();
+        let mut files = (None, None);
+        let (mut stdin, mut stdin_file) = (None, None);
+        for (name, value) in options.iter() {
+            match name {
+                "args" => parsed.args = RuntimeOptions::strings(&value, name)?,
+                "env" => parsed.env = Self::variables(&value)?,
+                "stdin" => stdin = Some(Self::input(&value)?),
+                "stdin_file" => stdin_file = Some(RuntimeOptions::string(&value, name)?),
+                "stdout" => parsed.stdout = Self::output_mode(&value, name)?,
+                "stderr" => parsed.stderr = Self::output_mode(&value, name)?,
+                "stdout_file" => files.0 = Some(RuntimeOptions::string(&value, name)?),
+                "stderr_file" => files.1 = Some(RuntimeOptions::string(&value, name)?),
+                "dirs" | "readonly_dirs" => {
+                    for (guest, host) in Self::named_strings(&value, name)? {
+                        let host = Self::expand_path(&host)?;
+                        parsed.preopens.push(Preopen { guest, host, writable: name == "dirs" });
+                    }
+                }
+                "files" => parsed.memory_files = Some(Self::files(&value)?),
+                "max_threads" => parsed.max_threads = Some(Self::max_threads(&value)?),
+                "" | "NA" => return Err("All WASI options must be named".to_string()),
+                other => return Err(format!("Unknown WASI option '{}'", other)),
+            }
+        }
+        match (stdin, stdin_file) {
+            (Some(_), Some(_)) => return Err("Options 'stdin' and 'stdin_file' cannot be combined".to_string()),
+            (Some(bytes), None) => parsed.stdin = StdinSource::Bytes(bytes),
+            (None, Some(path)) => parsed.stdin = StdinSource::File(path),
+            (None, None) => {}
+        }
+        if let Some(path) = files.0 {
+            if parsed.stdout != OutputMode::Capture {
+                return Err("Options 'stdout' and 'stdout_file' cannot be combined".to_string());
+            }
+            parsed.stdout = OutputMode::File(path);
+        }
+        if let Some(path) = files.1 {
+            if parsed.stderr != OutputMode::Capture {
+                return Err("Options 'stderr' and 'stderr_file' cannot be combined".to_string());
+            }
+            parsed.stderr = OutputMode::File(path);
+        }
+        if !parsed.preopens.is_empty() && parsed.memory_files.is_some() {
+            return Err("Option 'files' replaces the host filesystem and cannot be combined with 'dirs' or 'readonly_dirs'".to_string());
+        }
+        Ok(parsed)
+    }
+
+    /// Named character vector of environment variables
+    fn variables(value: &Robj) -> std::result::Result<Vec<(String, String)>, String> {
+        let variables = Self::named_strings(value, "env")?;
+        if let Some((name, _)) = variables.iter().find(|(name, _)| name.contains('=')) {
+            return Err(format!("Invalid environment variable name '{}'", name));
+        }
+        Ok(variables)
+    }
+
+    /// Name and value pairs of a character vector whose elements are all named
+    fn named_strings(value: &Robj, name: &str) -> std::result::Result<Vec<(String, String)>, String> {
+        let values = RuntimeOptions::strings(value, name)?;
+        if values.is_empty() {
+            return Ok(Vec::new());
+        }
+        let names: Vec<String> = match value.names() {
+            Some(names) => names.map(str::to_string).collect(),
+            None => return Err(format!("Option '{}' must be a named character vector", name)),
+        };
+        if names.iter().any(|n| n.is_empty() || n == "NA") {
+            return Err(format!("All elements of option '{}' must be named", name));
+        }
+        Ok(names.into_iter().zip(values).collect())
+    }
+
+    /// Host path with a leading `~` expanded to the home directory, as R's `path.expand()` does
+    fn expand_path(path: &str) -> std::result::Result<String, String> {
+        call!("path.expand", path)
+            .ok()
+            .and_then(|expanded| expanded.as_str().map(str::to_string))
+            .ok_or_else(|| format!("Cannot expand path '{}'", path))
+    }
+
+    /// Named list of file contents, each given like `stdin`
+    fn files(value: &Robj) -> std::result::Result<Vec<(String, Vec<u8>)>, String> {
+        let list = match value.as_list() {
+            Some(list) if list.is_empty() || list.names().is_some() => list,
+            _ => return Err("Option 'files' must be a named list of raw or character vectors".to_string()),
+        };
+        let mut files = Vec::with_capacity(list.len());
+        for (path, contents) in list.iter() {
+            if path.is_empty() || path == "NA" {
+                return Err("All files must be named by their path".to_string());
+            }
+            let contents = Self::input(&contents).map_err(|_| format!("File '{}' must be a raw or character vector", path))?;
+            files.push((path.to_string(), contents));
+        }
+        Ok(files)
+    }
+
+    /// stdin contents: a raw vector as is, or a character vector as lines each ending in a newline
+    fn input(value: &Robj) -> std::result::Result<Vec<u8>, String> {
+        if let Some(bytes) = value.as_raw() {
+            return Ok(bytes.as_slice().to_vec());
+        }
+        let lines = RuntimeOptions::strings(value, "stdin")
+            .map_err(|_| "Option 'stdin' must be a character or raw vector".to_string())?;
+        Ok(lines.iter().flat_map(|line| line.bytes().chain(std::iter::once(b'\n'))).collect())
+    }
+
+    /// Thread limit of a WASIX program, at least 1
+    fn max_threads(value: &Robj) -> std::result::Result<usize, String> {
+        match RuntimeOptions::count(value, "max_threads")? {
+            0 => Err("Option 'max_threads' must be at least 1".to_string()),
+            count => Ok(count as usize),
+        }
+    }
+
+    /// `"capture"` or `"console"`
+    fn output_mode(value: &Robj, name: &str) -> std::result::Result<OutputMode, String> {
+        match RuntimeOptions::string(value, name)?.as_str() {
+            "capture" => Ok(OutputMode::Capture),
+            "console" => Ok(OutputMode::Console),
+            other => Err(format!("Option '{}' must be \"capture\" or \"console\", got '{}'", name, other)),
+        }
+    }
+}
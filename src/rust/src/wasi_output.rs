@@ -0,0 +1,187 @@
+use extendr_api::prelude::*;
+use std::fs::File;
+use std::io::{self, Write};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::thread::{self, ThreadId};
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
+use wasmer_wasix::virtual_fs::{self, VirtualFile};
+
+/// The WASI output streams
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+impl OutputStream {
+    pub fn parse(name: &str) -> std::result::Result<Self, String> {
+        match name {
+            "stdout" => Ok(Self::Stdout),
+            "stderr" => Ok(Self::Stderr),
+            other => Err(format!("Unknown output stream '{}'; use \"stdout\" or \"stderr\"", other)),
+        }
+    }
+}
+
+/// How output is returned to R
+#[derive(Debug, Clone, Copy)]
+pub enum OutputFormat {
+    /// A single string, invalid UTF-8 being replaced
+    Character,
+    Raw,
+}
+
+impl OutputFormat {
+    pub fn parse(name: &str) -> std::result::Result<Self, String> {
+        match name {
+            "character" => Ok(Self::Character),
+            "raw" => Ok(Self::Raw),
+            other => Err(format!("format must be \"character\" or \"raw\", got '{}'", other)),
+        }
+    }
+
+    pub fn to_r(self, bytes: Vec<u8>) -> Robj {
+        match self {
+            Self::Character => r!(String::from_utf8_lossy(&bytes).into_owned()),
+            Self::Raw => r!(bytes),
+        }
+    }
+}
+
+/// Where the output of a WASI program goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Kept until read from R
+    Capture,
+    /// Printed to the R console as it is written, and kept until read from R
+    Console,
+    /// Written to a host file, created or truncated when the WASI state is created
+    File(String),
+}
+
+#[derive(Debug)]
+struct OutputState {
+    stream: OutputStream,
+    captured: Vec<u8>,
+    echo: bool,
+    file: Option<File>,
+    /// R's thread; console output written from other threads is only captured
+    r_thread: ThreadId,
+}
+
+/// stdout or stderr of a WASI environment.
+///
+/// Handed to the environment as a virtual file; clones share the output, so the runtime keeps
+/// one to read what the program wrote.
+#[derive(Debug, Clone)]
+pub struct WasiOutput {
+    state: Arc<Mutex<OutputState>>,
+}
+
+impl WasiOutput {
+    pub fn new(stream: OutputStream, mode: &OutputMode) -> std::result::Result<Self, String> {
+        let file = match mode {
+            OutputMode::File(path) => Some(File::create(path).map_err(|e| format!("Cannot create '{}': {}", path, e))?),
+            _ => None,
+        };
+        Ok(Self {
+            state: Arc::new(Mutex::new(OutputState {
+                stream,
+                captured: Vec::new(),
+                echo: *mode == OutputMode::Console,
+                file,
+                r_thread: thread::current().id(),
+            })),
+        })
+    }
+
+    /// Output written since the last call, which is cleared; always empty when redirected to a file
+    pub fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut self.state.lock().unwrap().captured)
+    }
+
+    fn write(&self, bytes: &[u8]) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(file) = &mut state.file {
+            return file.write_all(bytes);
+        }
+        if state.echo && thread::current().id() == state.r_thread {
+            let text = String::from_utf8_lossy(bytes);
+            match state.stream {
+                OutputStream::Stdout => rprint!("{}", text),
+                OutputStream::Stderr => reprint!("{}", text),
+            }
+        }
+        state.captured.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+impl VirtualFile for WasiOutput {
+    fn last_accessed(&self) -> u64 {
+        0
+    }
+
+    fn last_modified(&self) -> u64 {
+        0
+    }
+
+    fn created_time(&self) -> u64 {
+        0
+    }
+
+    fn size(&self) -> u64 {
+        0
+    }
+
+    fn set_len(&mut self, _new_size: u64) -> virtual_fs::Result<()> {
+        Ok(())
+    }
+
+    fn unlink(&mut self) -> virtual_fs::Result<()> {
+        Ok(())
+    }
+
+    fn poll_read_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(0))
+    }
+
+    fn poll_write_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(usize::MAX))
+    }
+}
+
+/// Output streams cannot be read back by the program
+impl AsyncRead for WasiOutput {
+    fn poll_read(self: Pin<&mut Self>, _cx: &mut Context<'_>, _buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for WasiOutput {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Poll::Ready(self.write(buf).map(|_| buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut state = self.state.lock().unwrap();
+        Poll::Ready(state.file.as_mut().map_or(Ok(()), |file| file.flush()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+/// Streams are not seekable; seeking is a no-op, as for a terminal
+impl AsyncSeek for WasiOutput {
+    fn start_seek(self: Pin<&mut Self>, _position: io::SeekFrom) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(0))
+    }
+}
@@ -1,35 +1,167 @@
 use std::sync::Arc;
-use wasmer::Store;
-use wasmer_wasix::WasiEnv;
+use wasmer::{Module, Store};
+use wasmer_wasix::capabilities::Capabilities;
+use wasmer_wasix::virtual_fs::mem_fs;
+use wasmer_wasix::virtual_net::LoopbackNetworking;
+use wasmer_wasix::{WasiEnv, WasiFunctionEnv, WasiVersion};
+use crate::wasi_fs::WasiFs;
+use crate::wasi_input::WasiInput;
+use crate::wasi_options::{StdinSource, WasiOptions};
+use crate::wasi_output::{OutputStream, WasiOutput};
+
+/// The system interface a WASI environment provides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WasiEnvType {
+    /// WASI preview 1 only
+    Wasi,
+    /// WASIX, extending WASI with threads, sockets, fork and exec
+    Wasix,
+}
+
+impl WasiEnvType {
+    pub fn parse(name: &str) -> std::result::Result<Self, String> {
+        match name {
+            "wasi" => Ok(Self::Wasi),
+            "wasix" => Ok(Self::Wasix),
+            other => Err(format!("Unknown WASI environment type '{}'; use \"wasi\" or \"wasix\"", other)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Wasi => "WASI",
+            Self::Wasix => "WASIX",
+        }
+    }
+
+    /// Whether a module importing the ABIs in `abis` can run in this environment
+    pub fn supports(self, abis: &[WasiVersion]) -> bool {
+        self == Self::Wasix || !abis.iter().any(|abi| matches!(abi, WasiVersion::Wasix32v1 | WasiVersion::Wasix64v1))
+    }
+}
+
+/// What the WASI environment of each WASI instance is created from
+#[derive(Debug, Clone)]
+pub struct WasiConfig {
+    /// Program name, the first argument the program sees
+    pub program: String,
+    pub env_type: WasiEnvType,
+    pub options: WasiOptions,
+}
+
+/// A WASI environment and the output streams of the program running in it, owned by one instance.
+/// Clones share the environment.
+#[derive(Clone)]
+pub struct WasiState {
+    pub env: WasiFunctionEnv,
+    pub env_type: WasiEnvType,
+    pub stdout: WasiOutput,
+    pub stderr: WasiOutput,
+    /// The in-memory filesystem the program sees instead of the host's, if it has one
+    pub memory_fs: Option<mem_fs::FileSystem>,
+}
+
+impl WasiState {
+    pub fn output(&self, stream: OutputStream) -> &WasiOutput {
+        match stream {
+            OutputStream::Stdout => &self.stdout,
+            OutputStream::Stderr => &self.stderr,
+        }
+    }
+}
 
 pub struct WasiUtils;
 
 impl WasiUtils {
-    /// Create a WASI environment with default settings (capturing stdout/stderr)
-    pub fn create_wasi_env(store: &mut Store, module_name: &str) -> std::result::Result<wasmer_wasix::WasiFunctionEnv, String> {
+    /// The WASI ABIs `module` imports, e.g. `wasi_snapshot_preview1` or `wasix_32v1`
+    pub fn abis(module: &Module) -> Vec<WasiVersion> {
+        wasmer_wasix::get_wasi_versions(module, false)
+            .map(|versions| versions.into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Check what can be checked of `config` before an environment is created from it: the
+    /// preopened directories and the paths of in-memory files
+    pub fn check(config: &WasiConfig) -> std::result::Result<(), String> {
+        config.options.preopens.iter().try_for_each(WasiFs::check_preopen)?;
+        match &config.options.memory_files {
+            Some(files) => WasiFs::check_files(files),
+            None => Ok(()),
+        }
+    }
+
+    /// Create a WASI environment with the arguments, environment variables and stdin given in
+    /// `options`, and stdout and stderr handled as they request. The host's stdin is never used.
+    /// The program sees the host directories preopened in `options`, or only an in-memory
+    /// filesystem mounted at `/` when `options` has files for one.
+    ///
+    /// A WASIX environment also lets the program spawn threads, open sockets on the loopback
+    /// interface only, and fork and exec, which the WASIX runtime emulates within the environment.
+    pub fn create_wasi_env(store: &mut Store, config: &WasiConfig) -> std::result::Result<WasiState, String> {
+        let (env_type, options) = (config.env_type, &config.options);
         // Create a pluggable runtime with the tokio task manager
-        let runtime = Arc::new(wasmer_wasix::PluggableRuntime::new(
+        let mut runtime = wasmer_wasix::PluggableRuntime::new(
             Arc::new(wasmer_wasix::runtime::task_manager::tokio::TokioTaskManager::new(
                 tokio::runtime::Handle::current()
             ))
-        ));
+        );
+        let mut capabilities = Capabilities::default();
+        match env_type {
+            WasiEnvType::Wasi => capabilities.threading.max_threads = Some(1),
+            WasiEnvType::Wasix => {
+                capabilities.threading.max_threads = options.max_threads;
+                capabilities.threading.enable_asynchronous_threading = true;
+                runtime.set_networking_implementation(LoopbackNetworking::new());
+            }
+        }
+        let runtime = Arc::new(runtime);
         
         // Create and configure the builder
-        let mut builder = WasiEnv::builder(module_name);
-        builder = builder.stdout(Box::new(wasmer_wasix::Pipe::new()));
-        builder = builder.stderr(Box::new(wasmer_wasix::Pipe::new()));
+        let mut builder = WasiEnv::builder(&config.program)
+            .capabilities(capabilities)
+            .args(&options.args)
+            .envs(options.env.iter().map(|(name, value)| (name, value)));
+        let stdin = match &options.stdin {
+            StdinSource::Bytes(bytes) => bytes.clone(),
+            StdinSource::File(path) => std::fs::read(path).map_err(|e| format!("Cannot read '{}': {}", path, e))?,
+        };
+        builder = builder.stdin(Box::new(WasiInput::new(stdin)));
+        let stdout = WasiOutput::new(OutputStream::Stdout, &options.stdout)?;
+        let stderr = WasiOutput::new(OutputStream::Stderr, &options.stderr)?;
+        builder = builder.stdout(Box::new(stdout.clone()));
+        builder = builder.stderr(Box::new(stderr.clone()));
+
+        for preopen in &options.preopens {
+            WasiFs::check_preopen(preopen)?;
+            builder
+                .add_preopen_build(|p| {
+                    p.directory(&preopen.host)
+                        .alias(&preopen.guest)
+                        .read(true)
+                        .write(preopen.writable)
+                        .create(preopen.writable)
+                })
+                .map_err(|e| format!("Cannot preopen '{}': {}", preopen.host, e))?;
+        }
+        let memory_fs = match &options.memory_files {
+            Some(files) => {
+                let fs = WasiFs::memory(files)?;
+                builder.set_fs(Box::new(fs.clone()));
+                builder
+                    .add_preopen_build(|p| p.directory("/").read(true).write(true).create(true))
+                    .map_err(|e| format!("Cannot mount the in-memory filesystem: {}", e))?;
+                Some(fs)
+            }
+            None => None,
+        };
         
         // Set the runtime (modifies builder in-place)
         builder.set_runtime(runtime);
         
         // Finalize the builder
         match builder.finalize(store) {
-            Ok(env) => Ok(env),
+            Ok(env) => Ok(WasiState { env, env_type, stdout, stderr, memory_fs }),
             Err(e) => Err(format!("Failed to create WASI state: {}", e)),
         }
     }
-
-    // Note: Reading stdout/stderr from WasiEnv is tricky because of ownership and locking.
-    // We might need to access the pipes directly if we kept them, or use the fs state.
-    // For now, let's try to access via state().
 }